@@ -0,0 +1,78 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Records the git commit and build time for the /version endpoint.
+// TULIP_GIT_HASH and SOURCE_DATE_EPOCH override them, e.g. for container builds without .git.
+// Also warns if the fonts and images served from assets/ have not been fetched.
+
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Served from assets/, the Content Security Policy blocks the CDNs they used to come from.
+const IMAGES: [&str; 1] = ["images/KLine_FairviewHeights_KimSchoenstadt2.jpeg"];
+
+/// Every file fonts.css and the pages load from assets/, by path under assets/.
+fn required_assets() -> Vec<String> {
+    let fonts_css = std::fs::read_to_string("assets/fonts/fonts.css").unwrap_or_default();
+
+    fonts_css
+        .split("url(\"/")
+        .skip(1)
+        .filter_map(|x| x.split('"').next())
+        .map(|x| x.to_string())
+        .chain(IMAGES.iter().map(|x| x.to_string()))
+        .collect()
+}
+
+fn check_assets() {
+    println!("cargo:rerun-if-changed=assets");
+
+    let missing = required_assets()
+        .into_iter()
+        .filter(|path| !Path::new("assets").join(path).is_file())
+        .collect::<Vec<_>>();
+
+    if missing.is_empty() {
+        return;
+    }
+
+    // pages still render without them, only with fallback fonts and no home image
+    println!(
+        "cargo:warning=assets/ is missing {}, run scripts/fetch_assets.sh and commit the files",
+        missing.join(", ")
+    );
+}
+
+fn main() {
+    check_assets();
+
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+    println!("cargo:rerun-if-env-changed=TULIP_GIT_HASH");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+
+    let git_hash = std::env::var("TULIP_GIT_HASH").ok().or_else(|| {
+        Command::new("git")
+            .args(["rev-parse", "--short=12", "HEAD"])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| String::from_utf8(output.stdout).ok())
+            .map(|hash| hash.trim().to_string())
+    });
+
+    let build_time = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|x| x.parse::<u64>().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|x| x.as_secs())
+                .unwrap_or(0)
+        });
+
+    println!("cargo:rustc-env=TULIP_GIT_HASH={}", git_hash.unwrap_or_else(|| String::from("unknown")));
+    println!("cargo:rustc-env=TULIP_BUILD_TIME={}", build_time);
+}
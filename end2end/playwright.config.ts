@@ -36,7 +36,9 @@ const config: PlaywrightTestConfig = {
     /* Maximum time each action such as `click()` can take. Defaults to 0 (no limit). */
     actionTimeout: 0,
     /* Base URL to use in actions like `await page.goto('/')`. */
-    // baseURL: 'http://localhost:3000',
+    baseURL: "http://localhost:3000",
+    /* The key manager is asserted against the English translations. */
+    locale: "en-US",
 
     /* Collect trace when retrying the failed test. See https://playwright.dev/docs/trace-viewer */
     trace: "on-first-retry",
@@ -98,10 +100,14 @@ const config: PlaywrightTestConfig = {
   // outputDir: 'test-results/',
 
   /* Run your local dev server before starting the tests */
-  // webServer: {
-  //   command: 'npm run start',
-  //   port: 3000,
-  // },
+  /* Tulip itself is started by cargo leptos end2end, with TULIP_CONFIG=end2end/tulip.e2e.ron */
+  webServer: {
+    command: "cargo run --bin mock_birch --features ssr",
+    cwd: "..",
+    url: "http://127.0.0.1:3100/",
+    timeout: 300 * 1000,
+    reuseExistingServer: !process.env.CI,
+  },
 };
 
 export default config;
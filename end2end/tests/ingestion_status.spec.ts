@@ -0,0 +1,33 @@
+import { test, expect } from "@playwright/test";
+
+test("explains why feeds are stale", async ({ page }) => {
+  await page.goto("/status");
+  await page.getByPlaceholder("Email").fill("viewer@example.com");
+  await page.getByPlaceholder("Password").fill("viewer-password");
+  await page.getByRole("button", { name: "Load" }).click();
+
+  await expect(page.getByText(/\d+ feeds, \d+ stale/)).toBeVisible();
+
+  const feed = (feedId: string) => page.locator("main > ul > li").filter({ has: page.getByRole("heading", { name: feedId }) });
+
+  await expect(feed("f-test~rt").getByText("✅ Fresh")).toBeVisible();
+
+  await expect(feed("f-broken~rt").getByText("⚠️ Stale")).toBeVisible();
+  await expect(feed("f-broken~rt").getByText("The agency rejected a request without a key with HTTP 401")).toBeVisible();
+
+  await expect(feed("f-9-bay~area~rt").getByText("The agency rate limited password #1 with HTTP 429")).toBeVisible();
+  await expect(feed("f-dqc-wmata~rt").getByText("not valid GTFS-RT: invalid wire type")).toBeVisible();
+});
+
+test("filters to stale feeds", async ({ page }) => {
+  await page.goto("/status?feed_id=f-test");
+  await page.getByPlaceholder("Email").fill("viewer@example.com");
+  await page.getByPlaceholder("Password").fill("viewer-password");
+  await page.getByRole("button", { name: "Load" }).click();
+
+  await expect(page.getByRole("heading", { name: "f-test~rt" })).toBeVisible();
+  await expect(page.getByRole("heading", { name: "f-broken~rt" })).toHaveCount(0);
+
+  await page.getByLabel("Stale feeds only").check();
+  await expect(page.getByRole("heading", { name: "f-test~rt" })).toHaveCount(0);
+});
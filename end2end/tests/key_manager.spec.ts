@@ -0,0 +1,157 @@
+import { test, expect, type Page } from "@playwright/test";
+
+const MOCK_BIRCH = "http://127.0.0.1:3100";
+
+async function login(page: Page, email: string, password: string) {
+  await page.goto("/realtimekeys");
+  await page.getByPlaceholder("Email").fill(email);
+  await page.getByPlaceholder("Password").fill(password);
+  await page.getByRole("button", { name: "Load" }).click();
+}
+
+async function loginAsEditor(page: Page) {
+  await login(page, "editor@example.com", "correct-horse-battery");
+  await expect(page.getByText("Authorised as Key Editor")).toBeVisible();
+}
+
+const feedIdInput = (page: Page) => page.locator('input[type="text"]').nth(0);
+const intervalInput = (page: Page) => page.locator('input[type="text"]').nth(1);
+const passwordInput = (page: Page) => page.locator("textarea");
+
+test("rejects a wrong password", async ({ page }) => {
+  await login(page, "editor@example.com", "not-the-password");
+
+  await expect(page.getByText("❌ Invalid email or password")).toBeVisible();
+  await expect(page.getByText("Not authorised")).toBeVisible();
+});
+
+test("lists feeds with their keys redacted", async ({ page }) => {
+  await loginAsEditor(page);
+
+  await expect(page.getByRole("heading", { name: "f-test~rt" })).toBeVisible();
+  await expect(page.getByRole("heading", { name: "f-broken~rt" })).toBeVisible();
+  await expect(page.getByText("e2e-secret-key")).toHaveCount(0);
+  await expect(page.getByText(/<redacted/).first()).toBeVisible();
+});
+
+test("viewers cannot see the submission form", async ({ page }) => {
+  await login(page, "viewer@example.com", "viewer-password");
+
+  await expect(page.getByText("Authorised as Viewer")).toBeVisible();
+  await expect(page.getByText("Your role can view feeds but cannot change keys.")).toBeVisible();
+  await expect(page.locator("textarea")).toHaveCount(0);
+});
+
+test("validates the interval", async ({ page }) => {
+  await loginAsEditor(page);
+
+  await intervalInput(page).fill("soon");
+  await expect(page.getByText("❌ Interval is invalid")).toBeVisible();
+
+  await intervalInput(page).fill("30s");
+  await expect(page.getByText("✅ Interval is valid, fetched every 30s")).toBeVisible();
+
+  await intervalInput(page).fill("1500ms");
+  await expect(page.getByText("✅ Interval is valid, fetched every 1500ms")).toBeVisible();
+
+  await intervalInput(page).fill("default");
+  await expect(page.getByText("✅ Interval is valid, Alpenrose's default of 10s")).toBeVisible();
+
+  await intervalInput(page).fill("100ms");
+  await expect(page.getByText("❌ Interval is invalid, must be at least 1s")).toBeVisible();
+
+  await intervalInput(page).fill("1h");
+  await expect(page.getByText("❌ Interval is invalid, use a duration")).toBeVisible();
+
+  // what the form took before, still accepted
+  await intervalInput(page).fill("Some(1000)");
+  await expect(page.getByText("✅ Interval is valid, fetched every 1s")).toBeVisible();
+});
+
+test("validates the password", async ({ page }) => {
+  await loginAsEditor(page);
+
+  await passwordInput(page).fill("not ron at all");
+  await expect(page.getByText("❌ Password is invalid", { exact: true })).toBeVisible();
+
+  await passwordInput(page).fill(
+    'Some((key_formats: [Header("api_key")], passwords: [(password: ["a", "b"], creator_email: "editor@example.com")], override_schedule_url: None, override_realtime_vehicle_positions: None, override_realtime_trip_updates: None, override_alerts: None))',
+  );
+  await expect(page.getByText("❌ Password is invalid, must be the same length as key format")).toBeVisible();
+
+  await passwordInput(page).fill("None");
+  await expect(page.getByText("✅ Password is valid")).toBeVisible();
+});
+
+test("imports a feed's keys by feed id", async ({ page }) => {
+  await loginAsEditor(page);
+
+  await feedIdInput(page).fill("f-unknown~rt");
+  await expect(page.getByText("❌ Feed ID is invalid")).toBeVisible();
+
+  await feedIdInput(page).fill("f-test~rt");
+  await expect(page.getByText("✅ Feed ID is valid")).toBeVisible();
+
+  await page.getByRole("button", { name: "Import using feed id" }).click();
+
+  await expect(passwordInput(page)).toHaveValue(/e2e-secret-key/);
+  await expect(intervalInput(page)).toHaveValue("5s");
+  await expect(page.getByText("✅ Password is valid")).toBeVisible();
+});
+
+test("submits keys to Birch", async ({ page, request }) => {
+  await loginAsEditor(page);
+
+  // a feed no other test reads, they run in parallel against the same mock
+
+  await feedIdInput(page).fill("f-dqc-wmata~rt");
+  await page.getByRole("button", { name: "Import using feed id" }).click();
+  await expect(passwordInput(page)).toHaveValue(/c3ab117ab77aa801f706e6bea12f5612/);
+  await intervalInput(page).fill("7s");
+
+  await page.getByRole("button", { name: "Submit" }).click();
+  await expect(page.getByText("✅ Submitted to Birch")).toBeVisible();
+
+  const response = await request.post(`${MOCK_BIRCH}/getrealtimekeys`, {
+    form: { email: "editor@example.com", password: "correct-horse-battery" },
+  });
+  const stored = (await response.json()).passwords["f-dqc-wmata~rt"];
+  expect(stored.fetch_interval_ms).toBe(7000);
+  expect(stored.passwords.passwords[0].password).toEqual(["c3ab117ab77aa801f706e6bea12f5612"]);
+});
+
+test("links to the feed's GTFS-RT data on the configured Birch", async ({ page, request }) => {
+  await loginAsEditor(page);
+
+  const link = page.getByRole("link", { name: "Json" }).first();
+  await expect(link).toHaveAttribute("href", /^http:\/\/127\.0\.0\.1:3100\/gtfs_rt\?/);
+
+  const response = await request.get((await link.getAttribute("href"))!);
+  expect(response.ok()).toBe(true);
+  expect((await response.json()).header.gtfs_realtime_version).toBe("2.0");
+});
+
+test("shows the config Alpenrose uses for a feed", async ({ page }) => {
+  await login(page, "viewer@example.com", "viewer-password");
+
+  const feed = page.locator("li").filter({ has: page.getByRole("heading", { name: "f-9-bay~area~rt" }) });
+  await feed.getByRole("button", { name: "Show Alpenrose config" }).click();
+
+  await expect(feed.getByText("Alpenrose fetches")).toBeVisible();
+  await expect(feed.getByText("http://api.511.org/transit/vehiclepositions")).toBeVisible();
+  await expect(feed.getByText("(override)")).toBeVisible();
+  await expect(feed.getByText("https://agency.example.com/f-9-bay~area~rt/trips")).toBeVisible();
+  await expect(feed.getByText("Every 10s (Alpenrose default)")).toBeVisible();
+  await expect(feed.getByText("URL query ?api_key=key 1")).toBeVisible();
+});
+
+test("shows Birch errors on submit", async ({ page }) => {
+  await loginAsEditor(page);
+
+  await feedIdInput(page).fill("f-broken~rt");
+  await intervalInput(page).fill("default");
+  await passwordInput(page).fill("None");
+
+  await page.getByRole("button", { name: "Submit" }).click();
+  await expect(page.getByText(/❌ .*Data did not submit correctly/)).toBeVisible();
+});
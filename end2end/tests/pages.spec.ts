@@ -0,0 +1,16 @@
+import { test, expect } from "@playwright/test";
+
+test("home page has its title and heading", async ({ page }) => {
+  await page.goto("/");
+
+  await expect(page).toHaveTitle("Welcome · Tulip");
+  await expect(page.locator("h1")).toHaveText("Welcome to Tulip!");
+});
+
+test("unknown pages return 404", async ({ page }) => {
+  const response = await page.goto("/this-page-does-not-exist");
+
+  expect(response?.status()).toBe(404);
+  await expect(page.getByText("404, this page doesn't exist")).toBeVisible();
+  await expect(page).toHaveTitle("Page not found · Tulip");
+});
@@ -0,0 +1,32 @@
+#!/bin/sh
+# Copyright Kyler Chin <kyler@catenarymaps.org>
+# Catenary Transit Initiatives
+# Attribution cannot be removed
+
+# Downloads the fonts, icons and home page image Tulip serves from assets/.
+# Run once when updating them and commit the results, Tulip itself never fetches from these hosts.
+
+set -eu
+
+cd "$(dirname "$0")/../assets"
+
+FONTSOURCE="https://cdn.jsdelivr.net/npm/@fontsource"
+
+fetch() {
+    echo "$2"
+    curl --fail --silent --show-error --location --output "$2" "$1"
+}
+
+for variant in 400-normal 400-italic 500-normal 600-normal 700-normal; do
+    fetch "$FONTSOURCE/barlow@5.1.0/files/barlow-latin-$variant.woff2" "fonts/barlow-latin-$variant.woff2"
+done
+
+for variant in 400-normal 700-normal; do
+    fetch "$FONTSOURCE/ibm-plex-mono@5.1.0/files/ibm-plex-mono-latin-$variant.woff2" "fonts/ibm-plex-mono-latin-$variant.woff2"
+done
+
+fetch "https://github.com/google/material-design-icons/raw/master/variablefont/MaterialSymbolsOutlined%5BFILL%2CGRAD%2Copsz%2Cwght%5D.woff2" \
+    "fonts/material-symbols-outlined.woff2"
+
+fetch "https://art.metro.net/wp-content/uploads/2022/09/KLine_FairviewHeights_KimSchoenstadt2-Large.jpeg" \
+    "images/KLine_FairviewHeights_KimSchoenstadt2.jpeg"
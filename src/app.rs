@@ -9,7 +9,6 @@ use chrono::DateTime;
 
 use chrono::offset::Utc;
 use chrono::prelude::*;
-use leptos::logging::*;
 
 use leptos::prelude::*;
 use leptos::reactive::graph::Source;
@@ -18,9 +17,22 @@ use leptos::*;
 use leptos_meta::*;
 use leptos_meta::*;
 use leptos_router::components::*;
-use leptos_router::path;
+use leptos_router::{SsrMode, path};
 use reactive_graph::graph::ToAnySource;
 use serde::{Deserialize, Serialize};
+use crate::approvals::Approvals;
+use crate::audit::Audit;
+use crate::auth::Role;
+use crate::effective_config::EffectiveConfigPanel;
+use crate::feeds::FeedPage;
+use crate::history::KeyHistory;
+use crate::ingestion::IngestionStatus;
+use crate::i18n::*;
+use crate::interval::{FetchInterval, IntervalError, MAX_FETCH_INTERVAL_MS, MIN_FETCH_INTERVAL_MS, format_ms};
+use crate::page_meta::PageMeta;
+use crate::rotation::{ExpiringKeys, RotationPanel};
+use crate::users::Users;
+use leptos_i18n::Locale as _;
 use std::borrow::Borrow;
 use std::collections::BTreeMap;
 use std::collections::HashMap;
@@ -31,34 +43,54 @@ use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::JsFuture;
 use web_sys::{Request, RequestInit, RequestMode, Response};
 
-static GTFSRAWOPTIONS: [(&str, &str); 3] = [
+pub(crate) static GTFSRAWOPTIONS: [(&str, &str); 3] = [
     ("Vehicles", "vehicle"),
     ("Trip Updates", "trip"),
     ("Alerts", "alert"),
 ];
 
+/// The HTML document every server rendered page is streamed into.
+pub fn shell(options: LeptosOptions) -> impl IntoView {
+    #[cfg(feature = "ssr")]
+    crate::security::provide_csp_nonce();
+
+    // set here rather than by a script, so the page never flashes the wrong theme
+    #[cfg(feature = "ssr")]
+    let theme = crate::theme::theme_from_request();
+    #[cfg(not(feature = "ssr"))]
+    let theme = crate::theme::Theme::default();
+
+    view! {
+        <!DOCTYPE html>
+        <html class=theme.html_class()>
+            <head>
+                <meta charset="utf-8" />
+                <meta name="viewport" content="width=device-width, initial-scale=1" />
+                <AutoReload options=options.clone() />
+                <HydrationScripts options=options.clone()/>
+                <MetaTags/>
+            </head>
+            <body><App />
+            </body>
+        </html>
+    }
+}
+
 #[component]
 pub fn App() -> impl IntoView {
     provide_meta_context();
+    crate::time::provide_time_preferences();
+    crate::theme::provide_theme();
 
     view! {
-        <script
-        inner_html={
-            "
-        if(localStorage.getItem('theme') === 'dark') {
-            document.querySelector('html').classList.add('dark');
-        }
-        "
-        }
-        />
-        <Stylesheet id="font" href="https://fonts.googleapis.com/css2?family=Barlow:ital,wght@0,100;0,200;0,300;0,400;0,500;0,600;0,700;0,800;0,900;1,100;1,200;1,300;1,400;1,500;1,600;1,700;1,800;1,900&family=IBM+Plex+Mono:ital,wght@0,100;0,200;0,300;0,400;0,500;0,600;0,700;1,100;1,200;1,300;1,400;1,500;1,600;1,700&display=swap" />
-        <Stylesheet id="icons" href="https://fonts.googleapis.com/css2?family=Material+Symbols+Outlined:opsz,wght,FILL,GRAD@20..48,100..700,0..1,-50..200" />
+        <Stylesheet id="font" href="/fonts/fonts.css" />
         <Stylesheet id="leptos" href="/pkg/catenarytulip.css"/>
 
         <Link rel="shortcut icon" href="/favicon.svg"/>
 
 
 
+        <I18nContextProvider>
         <Router  >
             <Routes fallback=|| {
                 view! {
@@ -69,9 +101,17 @@ pub fn App() -> impl IntoView {
                 <Route path=path!("/realtimekeys") view=move || view! { <RealtimeKeys /> }/>
                 <Route path=path!("/test1") view=move || view! { <Test /> }/>
                 <Route path=path!("/help") view=move || view! { <Help /> }/>
+                // rendered in full before sending, so crawlers see the feed without running the app
+                <Route path=path!("/feed/:feed_id") view=move || view! { <FeedPage /> } ssr=SsrMode::Async/>
+                <Route path=path!("/users") view=move || view! { <Users /> }/>
+                <Route path=path!("/audit") view=move || view! { <Audit /> }/>
+                <Route path=path!("/history") view=move || view! { <KeyHistory /> }/>
+                <Route path=path!("/approvals") view=move || view! { <Approvals /> }/>
+                <Route path=path!("/status") view=move || view! { <IngestionStatus /> }/>
                 <Route path=path!("/404.html") view=move || view! { <NotFound /> }/>
             </Routes>
         </Router>
+        </I18nContextProvider>
     }
 }
 
@@ -85,6 +125,7 @@ fn Test() -> impl IntoView {
     });
 
     view! {
+        <PageMeta title="Test" noindex=true/>
         <Nav/>
         <main class="m-8">
             <h1 class="text-2xl font-bold text-tulip">"Test"</h1>
@@ -107,56 +148,110 @@ fn Test() -> impl IntoView {
 }
 
 #[component]
-fn Nav() -> impl IntoView {
+pub(crate) fn Nav() -> impl IntoView {
+    let i18n = use_i18n();
+
     view! {
         <div class="sticky top-0 left-0 w-full bg-gray dark:bg-darksky p-4 border-b-2 border-tulip text-tulip flex flex-row justify-between">
             <a href="/">
                 <img alt="Tulip" src="/tulip.svg" class="h-12"/>
             </a>
             <div class="space-x-4 flex self-center">
-                <a href="/realtimekeys" class="material-symbols-outlined">
+                <a href="/realtimekeys" class="material-symbols-outlined" title=move || t_string!(i18n, nav_key_manager)>
                     "key"
                 </a>
-                <a href="/help" class="material-symbols-outlined">
-                    "help"
+                <a href="/users" class="material-symbols-outlined" title=move || t_string!(i18n, nav_operators)>
+                    "group"
                 </a>
-                <a href="#" class="material-symbols-outlined" onclick="document.querySelector('html').classList.toggle('dark'); window.localStorage.theme == 'dark' ? window.localStorage.theme = 'light' : window.localStorage.theme = 'dark'">
-                    "brightness_6"
+                <a href="/approvals" class="material-symbols-outlined" title=move || t_string!(i18n, nav_approvals)>
+                    "approval"
                 </a>
+                <a href="/status" class="material-symbols-outlined" title=move || t_string!(i18n, nav_status)>
+                    "monitor_heart"
+                </a>
+                <a href="/audit" class="material-symbols-outlined" title=move || t_string!(i18n, nav_audit)>
+                    "history"
+                </a>
+                <a href="/help" class="material-symbols-outlined" title=move || t_string!(i18n, nav_help)>
+                    "help"
+                </a>
+                <crate::theme::ThemeToggle/>
+                <crate::time::TimeZoneSelect/>
+                <LanguageSwitcher/>
             </div>
         </div>
     }
 }
 
+/// Lists each locale in its own language, the choice is kept in a cookie by leptos_i18n.
+#[component]
+fn LanguageSwitcher() -> impl IntoView {
+    let i18n = use_i18n();
+
+    view! {
+        <select
+            class="bg-gray dark:bg-darksky text-tulip font-semibold"
+            aria-label=move || t_string!(i18n, nav_language)
+            on:change=move |event| {
+                let value = event_target_value(&event);
+                if let Some(locale) = Locale::get_all().iter().find(|locale| locale.as_str() == value) {
+                    i18n.set_locale(*locale);
+                }
+            }
+        >
+            {
+                Locale::get_all().iter().map(|locale| {
+                    let locale = *locale;
+                    view! {
+                        <option value=locale.as_str() selected=move || i18n.get_locale() == locale>
+                            {td_string!(locale, language_name)}
+                        </option>
+                    }
+                }).collect_view()
+            }
+        </select>
+    }
+}
+
 #[component]
 fn Home() -> impl IntoView {
+    let i18n = use_i18n();
+
     view! {
+        <PageMeta title=move || t_string!(i18n, title_home) description=move || t_string!(i18n, description_home)/>
         <Nav/>
-        <img src="https://i0.wp.com/art.metro.net/wp-content/uploads/2022/09/KLine_FairviewHeights_KimSchoenstadt2-Large.jpeg" class="border-b-2 border-tulip w-[100vw] h-[450px] object-cover" style="z-index:-1;" />
+        <img src="/images/KLine_FairviewHeights_KimSchoenstadt2.jpeg" class="border-b-2 border-tulip w-[100vw] h-[450px] object-cover" style="z-index:-1;" />
         <span class="text-sm text-tulip m-2">"Kim Schoenstadt, "<i>"Inglewood CA Series: Metro collection 1-10"</i></span>
         <main class="m-8 text-center">
-            <h1 class="text-4xl font-bold text-tulip mb-8">"Welcome to Tulip!"</h1>
-            <a href="/realtimekeys" class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 mt-8 text-lg font-bold">"Realtime Key Manager"</a>
+            <h1 class="text-4xl font-bold text-tulip mb-8">{t!(i18n, home_welcome)}</h1>
+            <a href="/realtimekeys" class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 mt-8 text-lg font-bold">{t!(i18n, nav_key_manager)}</a>
         </main>
     }
 }
 
 #[component]
 fn Help() -> impl IntoView {
+    let i18n = use_i18n();
+
     view! {
+        <PageMeta title=move || t_string!(i18n, nav_help) description=move || t_string!(i18n, description_help)/>
         <Nav/>
         <main class="m-8">
-            <h1 class="text-2xl font-bold text-tulip mb-4">"Instructions"</h1>
-            <h1 class="text-xl font-bold text-tulip mb-2">"Realtime Key Manager"</h1>
-            <p> "Keys are defined as "<code class="mx-1">"Option<PasswordFormat>"</code>" as defined in this structure here:"</p>
+            <h1 class="text-2xl font-bold text-tulip mb-4">{t!(i18n, help_title)}</h1>
+            <h1 class="text-xl font-bold text-tulip mb-2">{t!(i18n, nav_key_manager)}</h1>
+            <p>{t!(i18n, help_keys_defined_as)}<code class="mx-1">"Option<PasswordFormat>"</code>{t!(i18n, help_as_defined_here)}</p>
             <div id="example-password h-[400px]"></div>
             <pre class="my-4 p-4 rounded-md bg-gray dark:bg-darksky text-wrap overflow-x-scroll"><code>{STRUCT_PASSWORD_TEXT.to_string()}</code></pre>
-            <p class="font-bold">"Every password entry is required to have the same length as key_format. Uploads will be blocked otherwise."</p>
-            <p>"The fetch interval is the number of milliseconds between fetches of the realtime data. Putting None will default the value to what Alpenrose has."</p>
+            <p class="font-bold">{t!(i18n, help_same_length)}</p>
+            <p>{t!(i18n, help_fetch_interval)}</p>
+            <p>{t!(i18n, help_rotation)}</p>
+            <p>{t!(i18n, help_rotation_strategy)}</p>
+            <p>{t!(i18n, help_approval)}</p>
+            <p>{t!(i18n, help_roles)}</p>
             <br />
-            <p>"Here's an imaginary entry for data from the Washington Metropolitan Area Transit Authority (WMATA):"</p>
+            <p>{t!(i18n, help_wmata_example)}</p>
             <pre class="my-4 p-4 rounded-md bg-gray dark:bg-darksky text-wrap overflow-x-scroll"><code>{format!("{}", ron::ser::to_string_pretty(&give_wmata_format(), ron::ser::PrettyConfig::default()).unwrap())}</code></pre>
-            <p>"Here's an imaginary entry for the San Francisco Bay Area data feed (Bay Area 511), but let's pretend we need to set the vehicle position url manually:"</p>
+            <p>{t!(i18n, help_sfbay_example)}</p>
             <pre class="my-4 p-4 rounded-md bg-gray dark:bg-darksky text-wrap overflow-x-scroll"><code>{format!("{}", ron::ser::to_string_pretty(&give_sfbay_format(), ron::ser::PrettyConfig::default()).unwrap())}</code></pre>
         </main>
     }
@@ -170,6 +265,8 @@ pub struct PasswordFormat {
     pub override_realtime_vehicle_positions: Option<String>,
     pub override_realtime_trip_updates: Option<String>,
     pub override_alerts: Option<String>,
+    #[serde(default, skip_serializing_if = "RotationStrategy::is_round_robin")]
+    pub rotation: RotationStrategy,
 }
 
 #[derive(Serialize, Clone, Deserialize, Debug, Hash, PartialEq, Eq)]
@@ -178,10 +275,46 @@ pub enum KeyFormat {
     UrlQuery(String),
 }
 
-#[derive(Serialize, Clone, Deserialize, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Clone, Deserialize, Debug, Hash, PartialEq, Eq, Default)]
 pub struct PasswordInfo {
     pub password: Vec<String>,
     pub creator_email: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agency_contact: Option<String>,
+    #[serde(default, skip_serializing_if = "KeyStatus::is_active")]
+    pub status: KeyStatus,
+    /// Only used by `RotationStrategy::Weighted`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<u32>,
+}
+
+#[derive(Serialize, Clone, Copy, Deserialize, Debug, Hash, PartialEq, Eq, Default)]
+pub enum KeyStatus {
+    #[default]
+    Active,
+    /// Being replaced, retire it once its replacement has passed a live probe.
+    Rotating,
+    /// Newly added and not yet probed.
+    Unverified,
+}
+
+/// How Alpenrose picks among a feed's passwords for each fetch.
+#[derive(Serialize, Clone, Copy, Deserialize, Debug, Hash, PartialEq, Eq, Default)]
+pub enum RotationStrategy {
+    /// Each password in turn.
+    #[default]
+    RoundRobin,
+    /// The first password until the agency answers 401 or 429, then the next.
+    Failover,
+    Random,
+    /// Random, in proportion to each password's `weight`.
+    Weighted,
 }
 
 #[derive(Serialize, Clone, Deserialize, Debug)]
@@ -202,103 +335,284 @@ pub struct KeyResponse {
     pub passwords: BTreeMap<String, EachPasswordRow>,
 }
 
+/// What Tulip sends to the browser after a successful login.
+/// Secret values are always redacted here, they are only sent by `reveal_realtime_key`.
+#[derive(Serialize, Clone, Deserialize, Debug)]
+pub struct AuthorisedKeys {
+    pub role: Role,
+    /// Where the GTFS-RT links point, `birch_url` from the config.
+    pub birch_url: String,
+    /// Alpenrose's fetch interval for feeds left on the default.
+    pub default_fetch_interval_ms: u32,
+    pub passwords: BTreeMap<String, EachPasswordRow>,
+}
+
+pub const REDACTED_PASSWORD: &str = "<redacted>";
+
+pub const FINGERPRINT_MIN_LENGTH: usize = 12;
+
+impl PasswordFormat {
+    fn map_passwords(&self, mask: impl Fn(&str) -> String) -> PasswordFormat {
+        PasswordFormat {
+            passwords: self
+                .passwords
+                .iter()
+                .map(|password_info| PasswordInfo {
+                    password: password_info.password.iter().map(|x| mask(x)).collect(),
+                    ..password_info.clone()
+                })
+                .collect(),
+            ..self.clone()
+        }
+    }
+
+    pub fn redacted(&self) -> PasswordFormat {
+        self.map_passwords(|_| String::from(REDACTED_PASSWORD))
+    }
+
+    /// Hides each secret but keeps its last 4 characters, so audit diffs still show which key changed.
+    /// Secrets shorter than `FINGERPRINT_MIN_LENGTH` are hidden entirely, as 4 characters would give away most of them.
+    pub fn fingerprinted(&self) -> PasswordFormat {
+        self.map_passwords(|password| {
+            let chars = password.chars().collect::<Vec<_>>();
+
+            if chars.len() < FINGERPRINT_MIN_LENGTH {
+                return String::from(REDACTED_PASSWORD);
+            }
+
+            let tail = chars[chars.len() - 4..].iter().collect::<String>();
+            format!("<redacted ...{}>", tail)
+        })
+    }
+
+    pub fn contains_redacted(&self) -> bool {
+        self.passwords
+            .iter()
+            .any(|password_info| password_info.password.iter().any(|x| x == REDACTED_PASSWORD))
+    }
+}
+
+impl KeyStatus {
+    pub fn is_active(&self) -> bool {
+        *self == KeyStatus::Active
+    }
+}
+
+impl RotationStrategy {
+    pub const ALL: [RotationStrategy; 4] = [
+        RotationStrategy::RoundRobin,
+        RotationStrategy::Failover,
+        RotationStrategy::Random,
+        RotationStrategy::Weighted,
+    ];
+
+    pub fn is_round_robin(&self) -> bool {
+        *self == RotationStrategy::RoundRobin
+    }
+}
+
+impl EachPasswordRow {
+    pub fn redacted(&self) -> EachPasswordRow {
+        EachPasswordRow {
+            passwords: self.passwords.as_ref().map(|x| x.redacted()),
+            fetch_interval_ms: self.fetch_interval_ms,
+        }
+    }
+
+    /// RON for the audit log, with secrets fingerprinted.
+    pub fn audit_text(&self) -> String {
+        let row = EachPasswordRow {
+            passwords: self.passwords.as_ref().map(|x| x.fingerprinted()),
+            fetch_interval_ms: self.fetch_interval_ms,
+        };
+
+        ron::ser::to_string_pretty(&row, ron::ser::PrettyConfig::default()).unwrap()
+    }
+}
+
+#[cfg(feature = "ssr")]
+async fn load_realtime_keys_checked(
+    master_email: &str,
+    master_password: &str,
+) -> Result<Option<AuthorisedKeys>, ServerFnError> {
+    let mut operator = match crate::auth::authenticate_verified(master_email, master_password).await? {
+        Some(operator) => operator,
+        None => return Ok(None),
+    };
+
+    match operator.keys().await? {
+        Some(key_response) => Ok(Some(AuthorisedKeys {
+            role: operator.role,
+            birch_url: crate::config::config().birch_url.clone(),
+            default_fetch_interval_ms: crate::config::config().alpenrose_default_fetch_interval_ms,
+            passwords: key_response
+                .passwords
+                .iter()
+                .map(|(feed_id, row)| (feed_id.clone(), row.redacted()))
+                .collect(),
+        })),
+        None => Ok(None),
+    }
+}
+
 #[server(endpoint = "load_realtime_keys")]
 pub async fn load_realtime_keys(
     master_email: String,
     master_password: String,
-) -> Result<Option<KeyResponse>, ServerFnError> {
-    let client = reqwest::Client::new();
+) -> Result<Option<AuthorisedKeys>, ServerFnError> {
+    use crate::audit::{AuditAction, AuditEntry, AuditOutcome};
 
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert("Content-Type", "application/x-www-form-urlencoded".parse()?);
+    let mut entry = AuditEntry::new(&master_email, AuditAction::Login, None);
+    let result = load_realtime_keys_checked(&master_email, &master_password).await;
+    entry.outcome = AuditOutcome::from_option(&result);
+    crate::audit::record(entry)?;
 
-    let mut params = std::collections::HashMap::new();
-    params.insert("email", master_email.as_str());
-    params.insert("password", master_password.as_str());
-
-    println!("Sending to Birch, {}, {}", master_email, master_password);
-
-    let response = client
-        .post("https://birch.catenarymaps.org/getrealtimekeys")
-        .headers(headers)
-        .form(&params)
-        .send()
-        .await?;
+    result
+}
 
-    println!("Sending to Birch");
-    match response.status() {
-        reqwest::StatusCode::OK => {
-            let response_text = response.text().await?;
-            println!("Recieved response back from birch");
+#[cfg(feature = "ssr")]
+async fn reveal_realtime_key_checked(
+    master_email: &str,
+    master_password: &str,
+    feed_id: &str,
+) -> Result<Option<EachPasswordRow>, ServerFnError> {
+    let mut operator = match crate::auth::authenticate_verified(master_email, master_password).await? {
+        Some(operator) => operator,
+        None => return Ok(None),
+    };
 
-            let key_response: KeyResponse = serde_json::from_str(&response_text)?;
+    crate::auth::require_role(&operator, Role::KeyEditor)?;
 
-            Ok(Some(key_response))
-        }
-        reqwest::StatusCode::UNAUTHORIZED => {
-            println!("Unauthorized");
-            Ok(None)
-        }
-        _ => {
-            println!("Error, {}", response.status());
-            Err(ServerFnError::new(format!("Error, {}, \n{}", response.status(), response.text().await.unwrap())))
-        }
+    match operator.keys().await? {
+        Some(mut key_response) => match key_response.passwords.remove(feed_id) {
+            Some(row) => Ok(Some(row)),
+            None => Err(ServerFnError::new(format!("No keys exist for feed {}", feed_id))),
+        },
+        None => Ok(None),
     }
 }
 
-#[server]
-async fn submit_data(
+#[server(endpoint = "reveal_realtime_key")]
+pub async fn reveal_realtime_key(
     master_email: String,
     master_password: String,
     feed_id: String,
-    password: String,
-    interval: String,
-) -> Result<bool, ServerFnError> {
+) -> Result<Option<EachPasswordRow>, ServerFnError> {
+    use crate::audit::{AuditAction, AuditEntry, AuditOutcome};
+
+    let mut entry = AuditEntry::new(&master_email, AuditAction::RevealKey, Some(feed_id.clone()));
+    let result = reveal_realtime_key_checked(&master_email, &master_password, &feed_id).await;
+    entry.outcome = AuditOutcome::from_option(&result);
+    crate::audit::record(entry)?;
+
+    result
+}
+
+#[derive(Serialize, Clone, Copy, Deserialize, Debug, PartialEq, Eq)]
+pub enum SubmitOutcome {
+    Applied,
+    /// The feed needs a second Key Editor's approval, this is the change request id.
+    PendingApproval(u64),
+    Unauthorised,
+}
+
+/// Fills in the audit entry's before and after as it goes, so failed submissions are recorded too.
+#[cfg(feature = "ssr")]
+async fn submit_data_checked(
+    entry: &mut crate::audit::AuditEntry,
+    master_email: &str,
+    master_password: &str,
+    feed_id: &str,
+    password: &str,
+    interval: &str,
+) -> Result<SubmitOutcome, ServerFnError> {
     //post json EachPasswordRow to /setrealtimekey/{feed_id}/
 
-    let client = reqwest::Client::new();
+    let mut operator = match crate::auth::authenticate_verified(master_email, master_password).await? {
+        Some(operator) => operator,
+        None => return Ok(SubmitOutcome::Unauthorised),
+    };
+
+    crate::auth::require_role(&operator, Role::KeyEditor)?;
 
     let data_to_send = EachPasswordRow {
-        passwords: ron::from_str(&password)?,
-        fetch_interval_ms: ron::from_str(&interval)?,
+        passwords: ron::from_str(password)?,
+        fetch_interval_ms: FetchInterval::parse(interval)
+            .map_err(|err| ServerFnError::new(err.to_string()))?
+            .to_birch(),
     };
 
-    let response = client
-        .post(format!(
-            "https://birch.catenarymaps.org/setrealtimekey/{}/",
-            feed_id
-        ))
-        .header("email", master_email)
-        .header("password", master_password)
-        .body(ron::ser::to_string(&data_to_send)?)
-        .send()
-        .await?;
-
-    let status = response.status();
-
-    match status {
-        reqwest::StatusCode::OK => Ok(true),
-        reqwest::StatusCode::UNAUTHORIZED => Ok(false),
-        _ => {
-            let text = response.text().await;
-
-            match text {
-                Ok(text) => {
-                    println!(
-                        "recieved strange answer from birch on setrealtimekey, {} text {}",
-                        status, text
-                    );
-                }
-                Err(err) => {
-                    println!("error on birch setrealtimekey {} err {}", status, err);
-                }
-            }
-            Err(ServerFnError::new("Data did not submit correctly"))
+    //never overwrite real keys with the placeholder shown to the browser
+    if let Some(passwords) = &data_to_send.passwords {
+        if passwords.contains_redacted() {
+            entry.after = Some(data_to_send.audit_text());
+            return Err(ServerFnError::new(
+                "Password contains redacted values, reveal the existing keys before editing them",
+            ));
         }
+
+        crate::rotation::validate_rotation(passwords).map_err(ServerFnError::new)?;
+    }
+
+    if crate::approvals::requires_approval(feed_id) {
+        entry.action = crate::audit::AuditAction::RequestChange;
+        entry.after = Some(data_to_send.audit_text());
+        let id = crate::approvals::create_change_request(feed_id, &operator.email, &data_to_send)?;
+        return Ok(SubmitOutcome::PendingApproval(id));
+    }
+
+    match apply_key_change(&mut operator, entry, feed_id, &data_to_send, "Submitted").await? {
+        true => Ok(SubmitOutcome::Applied),
+        false => Ok(SubmitOutcome::Unauthorised),
     }
 }
 
-const STRUCT_PASSWORD_TEXT: &str = r"#[derive(Serialize, Clone, Deserialize, Debug, Hash, PartialEq, Eq, Default)]
+/// Sends a feed's new keys to Birch, filling in the audit entry and keeping the version in the key history.
+#[cfg(feature = "ssr")]
+pub(crate) async fn apply_key_change(
+    operator: &mut crate::auth::Operator,
+    entry: &mut crate::audit::AuditEntry,
+    feed_id: &str,
+    data_to_send: &EachPasswordRow,
+    note: &str,
+) -> Result<bool, ServerFnError> {
+    entry.after = Some(data_to_send.audit_text());
+
+    let before = match operator.keys().await? {
+        Some(mut key_response) => key_response.passwords.remove(feed_id),
+        None => return Ok(false),
+    };
+
+    entry.before = before.as_ref().map(|x| x.audit_text());
+
+    let submitted = crate::birch::set_key(&operator.birch_email, &operator.birch_password, feed_id, data_to_send).await?;
+
+    if submitted {
+        crate::history::record_version(feed_id, &operator.email, note, before.as_ref(), data_to_send)?;
+    }
+
+    Ok(submitted)
+}
+
+#[server]
+pub async fn submit_data(
+    master_email: String,
+    master_password: String,
+    feed_id: String,
+    password: String,
+    interval: String,
+) -> Result<SubmitOutcome, ServerFnError> {
+    use crate::audit::{AuditAction, AuditEntry, AuditOutcome};
+
+    let mut entry = AuditEntry::new(&master_email, AuditAction::SubmitKeys, Some(feed_id.clone()));
+    let result = submit_data_checked(&mut entry, &master_email, &master_password, &feed_id, &password, &interval).await;
+    entry.outcome = AuditOutcome::from_bool(&result.clone().map(|x| x != SubmitOutcome::Unauthorised));
+    crate::audit::record(entry)?;
+
+    result
+}
+
+const STRUCT_PASSWORD_TEXT: &str = r##"#[derive(Serialize, Clone, Deserialize, Debug, Hash, PartialEq, Eq, Default)]
 pub struct PasswordFormat {
     pub key_formats: Vec<KeyFormat>,
     pub passwords: Vec<PasswordInfo>,
@@ -306,6 +620,8 @@ pub struct PasswordFormat {
     pub override_realtime_vehicle_positions: Option<String>,
     pub override_realtime_trip_updates: Option<String>,
     pub override_alerts: Option<String>,
+    #[serde(default, skip_serializing_if = "RotationStrategy::is_round_robin")]
+    pub rotation: RotationStrategy,
 }
 
 #[derive(Serialize, Clone, Deserialize, Debug, Hash, PartialEq, Eq)]
@@ -314,37 +630,80 @@ pub enum KeyFormat {
     UrlQuery(String),
 }
 
-#[derive(Serialize, Clone, Deserialize, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Clone, Deserialize, Debug, Hash, PartialEq, Eq, Default)]
 pub struct PasswordInfo {
     pub password: Vec<String>,
     pub creator_email: String,
-}";
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agency_contact: Option<String>,
+    #[serde(default, skip_serializing_if = "KeyStatus::is_active")]
+    pub status: KeyStatus,
+    /// Only used by `RotationStrategy::Weighted`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<u32>,
+}
 
-fn give_wmata_format() -> Option<PasswordFormat> {
+#[derive(Serialize, Clone, Copy, Deserialize, Debug, Hash, PartialEq, Eq, Default)]
+pub enum KeyStatus {
+    #[default]
+    Active,
+    /// Being replaced, retire it once its replacement has passed a live probe.
+    Rotating,
+    /// Newly added and not yet probed.
+    Unverified,
+}
+
+/// How Alpenrose picks among a feed's passwords for each fetch.
+#[derive(Serialize, Clone, Copy, Deserialize, Debug, Hash, PartialEq, Eq, Default)]
+pub enum RotationStrategy {
+    /// Each password in turn.
+    #[default]
+    RoundRobin,
+    /// The first password until the agency answers 401 or 429, then the next.
+    Failover,
+    Random,
+    /// Random, in proportion to each password's `weight`.
+    Weighted,
+}"##;
+
+pub(crate) fn give_wmata_format() -> Option<PasswordFormat> {
     Some(PasswordFormat {
         key_formats: vec![KeyFormat::Header("api_key".to_string())],
         passwords: vec![PasswordInfo {
             password: vec!["c3ab117ab77aa801f706e6bea12f5612".to_string()],
             creator_email: String::from("kyler@catenarymaps.org"),
+            created_at: NaiveDate::from_ymd_opt(2024, 3, 1),
+            expires_at: NaiveDate::from_ymd_opt(2025, 3, 1),
+            agency_contact: Some(String::from("developer@wmata.com")),
+            ..Default::default()
         }],
         override_schedule_url: None,
         override_realtime_vehicle_positions: None,
         override_realtime_trip_updates: None,
         override_alerts: None,
+        rotation: RotationStrategy::RoundRobin,
     })
 }
 
-fn give_sfbay_format() -> Option<PasswordFormat> {
+pub(crate) fn give_sfbay_format() -> Option<PasswordFormat> {
     Some(PasswordFormat {
         key_formats: vec![KeyFormat::UrlQuery("api_key".to_string())],
         passwords: vec![
             PasswordInfo {
                 password: vec!["f8f683cc177053581ef9d425071eb6d1".to_string()],
                 creator_email: String::from("kyler@catenarymaps.org"),
+                ..Default::default()
             },
             PasswordInfo {
                 password: vec!["e6c335d9cab3bd41ac51bc6235ce966b".to_string()],
                 creator_email: String::from("sam@catenarymaps.org"),
+                ..Default::default()
             },
         ],
         override_schedule_url: None,
@@ -353,15 +712,10 @@ fn give_sfbay_format() -> Option<PasswordFormat> {
         )),
         override_realtime_trip_updates: None,
         override_alerts: None,
+        rotation: RotationStrategy::Failover,
     })
 }
 
-fn time_format_now() -> String {
-    let system_time = Local::now();
-
-    format!("{}", system_time.format("%A %+"))
-}
-
 #[component]
 fn NotFound() -> impl IntoView {
     #[cfg(feature = "ssr")]
@@ -372,16 +726,21 @@ fn NotFound() -> impl IntoView {
         resp.set_status(actix_web::http::StatusCode::NOT_FOUND);
     }
 
+    let i18n = use_i18n();
+
     view! {
+        <PageMeta title=move || t_string!(i18n, title_not_found) noindex=true/>
         <Nav/>
         <main class="h-full w-full">
-            <div class="m-auto">"404, this page doesn't exist"</div>
+            <div class="m-auto">{t!(i18n, not_found)}</div>
         </main>
     }
 }
 
 #[component]
 fn RealtimeKeys() -> impl IntoView {
+    let i18n = use_i18n();
+
     let (master_email, set_master_email) = signal(String::from(""));
     let (master_password, set_master_password) = signal(String::from(""));
     let (form_feed_id, set_form_feed_id) = signal(String::from(""));
@@ -390,36 +749,45 @@ fn RealtimeKeys() -> impl IntoView {
 
     let original_keys: RwSignal<BTreeMap<String, EachPasswordRow>> = RwSignal::new(BTreeMap::new());
 
+    // secrets fetched through reveal_realtime_key, keyed by feed id
+    let revealed_keys: RwSignal<BTreeMap<String, Option<PasswordFormat>>> = RwSignal::new(BTreeMap::new());
+
     let (authorised, set_authorised) = signal(false);
+    let (role, set_role) = signal(None::<Role>);
+    let (birch_url, set_birch_url) = signal(String::from("https://birch.catenarymaps.org"));
+    let (default_interval_ms, set_default_interval_ms) = signal(0u32);
+
+    let can_reveal = move || role.get().map(|x| x.can_reveal_secrets()).unwrap_or(false);
+    let can_edit = move || role.get().map(|x| x.can_edit_keys()).unwrap_or(false);
 
+    let (submit_message, set_submit_message) = signal(String::from(""));
+    let (load_error, set_load_error) = signal(None::<String>);
+
+    // only set when the login form is submitted, so partial credentials are never sent
+    let (credentials, set_credentials) = signal(None::<(String, String)>);
+    let session_email = Signal::derive(move || credentials.get().map(|(email, _)| email).unwrap_or_default());
+    let session_password = Signal::derive(move || credentials.get().map(|(_, password)| password).unwrap_or_default());
+
+    let (loading, set_loading) = signal(false);
+
+    //bumped to reload the keys with the same credentials
     let (count, set_count) = signal(0);
 
     let async_data_load = ArcLocalResource::new(move || {
-        let master_email = master_email.get().clone();
-        let master_password = master_password.get().clone();
+        let credentials = credentials.get();
         let count = count.get();
 
         leptos_dom::log!("Count {}", count);
 
-        leptos_dom::log!("Creds {} {}", master_email, master_password);
-        
+        async move {
+            let (master_email, master_password) = credentials?;
 
-        async {
-            if master_email.is_empty() || master_password.is_empty() {
-                return None;
-            }
             leptos_dom::log!("Try loading passwords");
+            set_loading.set(true);
             let fetch = load_realtime_keys(master_email, master_password).await;
+            set_loading.set(false);
 
-            leptos_dom::log!("fetch {:#?}", fetch);
-
-            match fetch {
-                Ok(data) => data,
-                Err(err) => {
-                    leptos_dom::log!("Error {:#?}", err);
-                    None
-                }
-            }
+            Some(fetch)
         }
     });
 
@@ -427,26 +795,36 @@ fn RealtimeKeys() -> impl IntoView {
     let password_node_ref: NodeRef<html::Textarea> = NodeRef::new();
     let interval_ms_node_ref: NodeRef<html::Input> = NodeRef::new();
 
-    let async_part_left = (&async_data_load).clone();
-
-    Effect::new(move || {
-        let count = count.get();
-        async_part_left.refetch();
-    });
-
     let async_part_right = (&async_data_load).clone();
 
     Effect::new(move || {
         let data = async_part_right.try_read();
 
         if let Some(data) = data {
-            log!("GOT THE GUARD");
             if let Some(data) = &*data {
-                let data = data.deref();
-
-                if let Some(data) = data {
-                    original_keys.update(|x| *x = data.passwords.clone());
-                    set_authorised.set(true);
+                match data.deref() {
+                    Some(Ok(Some(data))) => {
+                        original_keys.update(|x| *x = data.passwords.clone());
+                        revealed_keys.update(|x| x.clear());
+                        set_role.set(Some(data.role));
+                        set_birch_url.set(data.birch_url.clone());
+                        set_default_interval_ms.set(data.default_fetch_interval_ms);
+                        set_authorised.set(true);
+                        set_load_error.set(None);
+                    }
+                    Some(Ok(None)) => {
+                        original_keys.update(|x| x.clear());
+                        revealed_keys.update(|x| x.clear());
+                        set_role.set(None);
+                        set_authorised.set(false);
+                        set_load_error.set(Some(t_string!(i18n, invalid_login).to_string()));
+                    }
+                    Some(Err(err)) => {
+                        leptos_dom::log!("Error {:#?}", err);
+                        //shows lockouts and rate limits as well as Birch errors
+                        set_load_error.set(Some(err.to_string()));
+                    }
+                    None => {}
                 }
             }
         } else {
@@ -455,53 +833,53 @@ fn RealtimeKeys() -> impl IntoView {
     });
 
     view! {
+        <PageMeta title=move || t_string!(i18n, nav_key_manager) noindex=true/>
         <Nav/>
         <main class="p-8">
-            <h1 class="text-2xl font-bold text-tulip">"Realtime Key Manager"</h1>
-            <p>"Please confirm your Tulip login credentials, as key information is sensitive and confidential."</p>
+            <h1 class="text-2xl font-bold text-tulip">{t!(i18n, nav_key_manager)}</h1>
+            <p>{t!(i18n, keys_login_prompt)}</p>
 
-            <form  onsubmit="return false;">
+            <form on:submit=move |event| {
+                event.prevent_default();
+                leptos::logging::log!("LOAD button pressed");
+                set_credentials.set(Some((master_email.get(), master_password.get())));
+            }>
             <input
                 type="email"
-                placeholder="Email"
+                placeholder=move || t_string!(i18n, email)
                 prop:value=move || master_email.get()
                 class= "bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold mr-4"
                 on:input=move |event| {
                     set_master_email.set(event_target_value(&event));
-                    set_count.set(count.get() + 1);
                 }
             />
             <input
                 type="password"
                 autocomplete="current-password"
-                placeholder="Password"
+                placeholder=move || t_string!(i18n, password)
                 prop:value=move || master_password.get()
                 class= "bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold"
                 on:input=move |event| {
-                    leptos::logging::log!("Password input");
                     set_master_password.set(event_target_value(&event));
-                    set_count.set(count.get() + 1);
                 }
             />
 
             <br/>
-            <button class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold"
-            on:input=move |event| {
-                //async_data_load.refetch();
-                leptos::logging::log!("LOAD button pressed");
-                set_count.set(count.get() + 1);
-            }
-            >"Load"</button>
+            <button
+            type="submit"
+            class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold"
+            disabled=move || loading.get() || master_email.get().is_empty() || master_password.get().is_empty()
+            >{move || if loading.get() { t_string!(i18n, loading) } else { t_string!(i18n, load) }}</button>
             </form>
 
-            <p>"COUNT" {move || count.get()}</p>
+            {move || load_error.get().map(|err| view! { <p class="font-semibold">{format!("❌ {}", err)}</p> })}
 
             <br/>
                 {
                     move || if authorised.get() {
                         view! {
-                            <p>"Authorised"</p>
-                            <h2 class="text-xl font-semibold">"Realtime Keys"</h2>
+                            <p>{t!(i18n, authorised_as, role = move || role.get().map(|x| crate::users::role_label(i18n, x)).unwrap_or(""))}</p>
+                            <h2 class="text-xl font-semibold">{t!(i18n, realtime_keys)}</h2>
 
                     //reload button
                     <button
@@ -511,7 +889,7 @@ fn RealtimeKeys() -> impl IntoView {
                     }
                     class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold"
                     >
-                        "Reload"
+                        {t!(i18n, reload)}
                     </button>
 
                     <ul class="grid grid-cols-1 md:grid-cols-2 lg:grid-cols-3 gap-4">
@@ -524,19 +902,63 @@ fn RealtimeKeys() -> impl IntoView {
                                         {
                                             GTFSRAWOPTIONS.iter().map(|(name_of_feed_type, feed_type)|  view! {
                                                 <p class="font-semibold">{name_of_feed_type.to_string()} {" "}
-                                                <a class="underline text-blue-500 dark:text-blue-300" href={format!("https://birch.catenarymaps.org/gtfs_rt?feed_id={}&feed_type={}", key.clone(), feed_type.clone())}>"Protobuf"</a>
+                                                <a class="underline text-blue-500 dark:text-blue-300" href={format!("{}/gtfs_rt?feed_id={}&feed_type={}", birch_url.get(), key, *feed_type)}>"Protobuf"</a>
                                                 {" "}
-                                                <a class="underline text-blue-500 dark:text-blue-300" href={format!("https://birch.catenarymaps.org/gtfs_rt?feed_id={}&feed_type={}&format=json", key.clone(), feed_type.clone())}>"Json"</a>
-                                                {" "}<a class="underline text-blue-500 dark:text-blue-300" href={format!("https://birch.catenarymaps.org/gtfs_rt?feed_id={}&feed_type={}&format=ron", key.clone(), feed_type.clone())}>"Ron"</a>
+                                                <a class="underline text-blue-500 dark:text-blue-300" href={format!("{}/gtfs_rt?feed_id={}&feed_type={}&format=json", birch_url.get(), key, *feed_type)}>"Json"</a>
+                                                {" "}<a class="underline text-blue-500 dark:text-blue-300" href={format!("{}/gtfs_rt?feed_id={}&feed_type={}&format=ron", birch_url.get(), key, *feed_type)}>"Ron"</a>
                                                 </p>
                                             }).collect_view()
                                         }
 
-                                        <p class="font-semibold">"Passwords:"</p>
-                                        <pre class="my-4 p-4 rounded-md bg-gray dark:bg-darksky text-wrap overflow-scroll h-[300px]"><code>{format!("{}", ron::ser::to_string_pretty(&value.passwords,
-                                            ron::ser::PrettyConfig::default()).unwrap())}</code></pre>
-                                        <p class="font-semibold">"Fetch Interval:"</p>
-                                        <p>{format!("{:?}", value.fetch_interval_ms)}</p>
+                                        <p class="font-semibold">{t!(i18n, passwords)}</p>
+                                        {
+                                            let feed_id = key.clone();
+                                            let redacted = value.passwords.clone();
+                                            move || {
+                                                let shown = revealed_keys.with(|x| x.get(&feed_id).cloned()).unwrap_or(redacted.clone());
+                                                view! {
+                                                    <pre class="my-4 p-4 rounded-md bg-gray dark:bg-darksky text-wrap overflow-scroll h-[300px]"><code>{ron::ser::to_string_pretty(&shown,
+                                                        ron::ser::PrettyConfig::default()).unwrap()}</code></pre>
+                                                }
+                                            }
+                                        }
+                                        {
+                                            let feed_id = key.clone();
+                                            move || can_reveal().then(|| {
+                                                let feed_id = feed_id.clone();
+                                                view! {
+                                                    <button
+                                                    class="bg-gray dark:bg-darksky rounded-md p-1 px-2 border-2 border-tulip text-sm font-bold"
+                                                    on:click=move |_| {
+                                                        let feed_id = feed_id.clone();
+                                                        let (master_email, master_password) = (session_email.get(), session_password.get());
+                                                        spawn_local(async move {
+                                                            match reveal_realtime_key(master_email, master_password, feed_id.clone()).await {
+                                                                Ok(Some(row)) => revealed_keys.update(|x| { x.insert(feed_id, row.passwords); }),
+                                                                Ok(None) => set_authorised.set(false),
+                                                                Err(err) => leptos_dom::log!("Reveal failed {:#?}", err),
+                                                            }
+                                                        });
+                                                    }
+                                                    >{t!(i18n, reveal)}</button>
+                                                }
+                                            })
+                                        }
+                                        <p class="font-semibold">{t!(i18n, fetch_interval)}</p>
+                                        <p>{
+                                            let interval = FetchInterval::from_birch(value.fetch_interval_ms);
+                                            move || match interval {
+                                                FetchInterval::Default => t_string!(i18n, interval_default, interval = format_ms(default_interval_ms.get())).to_string(),
+                                                FetchInterval::Every { ms } => t_string!(i18n, interval_every, interval = format_ms(ms)).to_string(),
+                                            }
+                                        }</p>
+                                        <EffectiveConfigPanel feed_id=key.clone() master_email=session_email master_password=session_password/>
+                                        {
+                                            let feed_id = key.clone();
+                                            move || can_edit().then(|| view! {
+                                                <a class="underline text-blue-500 dark:text-blue-300" href={format!("/history?feed_id={}", feed_id)}>{t!(i18n, history)}</a>
+                                            })
+                                        }
                                     </li>
                                 }
                             }).collect_view())
@@ -544,8 +966,13 @@ fn RealtimeKeys() -> impl IntoView {
 
                     </ul>
 
+                    <ExpiringKeys keys=original_keys/>
+
+                {
+                    move || if can_edit() {
+                        view! {
                 <div><h2 class="text-xl font-semibold">
-                "Submission form"
+                {t!(i18n, submission_form)}
                 </h2></div>
 
                 <div class="flex flex-row gap-x-2">
@@ -556,8 +983,8 @@ fn RealtimeKeys() -> impl IntoView {
                         set_form_interval_ms.set(String::from(""));
                         set_form_password.set(String::from(""));
                     }
-                    disabled=move || !authorised.get()
-                     >"Clear all fields"</button>
+                    disabled=move || !can_edit()
+                     >{t!(i18n, clear_all_fields)}</button>
 
                         <button class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold"
                         on:click=move |_| {
@@ -567,43 +994,50 @@ fn RealtimeKeys() -> impl IntoView {
                                     ron::ser::PrettyConfig::default()).unwrap()
                         ));
                         }
-                        disabled=move || !authorised.get()
+                        disabled=move || !can_edit()
                         >
-                        "Fill with default password format"
+                        {t!(i18n, fill_default_format)}
                     </button>
 
                     <button class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold"
                         on:click=move |_| {
-                            match original_keys.get().get(form_feed_id.get().as_str()) {
-                                Some(original_data) => {
-                                    set_form_password.set(
-                                        //use ron
-                                        ron::ser::to_string_pretty(&original_data.passwords,
-                                            ron::ser::PrettyConfig::default()).unwrap(),
-                                    );
-                                    set_form_interval_ms.set(
-                                        ron::ser::to_string_pretty(&original_data.fetch_interval_ms,
-                                            ron::ser::PrettyConfig::default()).unwrap(),
-                                    );
-                                },
-                                None => {
-                                    set_form_password.set(String::from(""));
-                                    set_form_interval_ms.set(String::from(""));
+                            let feed_id = form_feed_id.get();
+                            let (master_email, master_password) = (session_email.get(), session_password.get());
+
+                            //the listing only holds redacted keys, so fetch the real ones to edit
+                            spawn_local(async move {
+                                match reveal_realtime_key(master_email, master_password, feed_id.clone()).await {
+                                    Ok(Some(original_data)) => {
+                                        set_form_password.set(
+                                            //use ron
+                                            ron::ser::to_string_pretty(&original_data.passwords,
+                                                ron::ser::PrettyConfig::default()).unwrap(),
+                                        );
+                                        set_form_interval_ms.set(
+                                            FetchInterval::from_birch(original_data.fetch_interval_ms).to_string(),
+                                        );
+                                        revealed_keys.update(|x| { x.insert(feed_id, original_data.passwords); });
+                                    },
+                                    Ok(None) => set_authorised.set(false),
+                                    Err(_) => {
+                                        set_form_password.set(String::from(""));
+                                        set_form_interval_ms.set(String::from(""));
+                                    }
                                 }
-                            }
+                            });
                         }
-                        disabled=move || !authorised.get()
+                        disabled=move || !can_edit()
                         >
-                        "Import using feed id"
+                        {t!(i18n, import_using_feed_id)}
                     </button>
                 </div>
 
-                <p>"feed id"</p>
+                <p>{t!(i18n, form_feed_id)}</p>
 
                 <input
                 type="text"
                 prop:value=move || form_feed_id.get()
-                disabled=move || !authorised.get()
+                disabled=move || !can_edit()
                 class= "bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold"
                 on:input=move |event| {
                     set_form_feed_id.set(event_target_value(&event));
@@ -617,25 +1051,25 @@ fn RealtimeKeys() -> impl IntoView {
                 move || match original_keys.get().get(form_feed_id.get().as_str()) {
                     Some(_) => view! {
 
-                        <p>{String::from("✅ Feed ID is valid")}</p>
+                        <p>{t!(i18n, feed_id_valid)}</p>
 
-                    },
+                    }.into_any(),
                     None => view! {
 
-                            <p>{String::from("❌ Feed ID is invalid")}</p>
+                            <p>{t!(i18n, feed_id_invalid)}</p>
 
 
-                    }
+                    }.into_any()
                 }
             }
 
-            <p>"interval"</p>
+            <p>{t!(i18n, form_interval)}</p>
 
             <input
                 type="text"
                 prop:value=move || form_interval_ms.get()
                 class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold"
-                disabled=move || !authorised.get()
+                disabled=move || !can_edit()
                 on:input=move |event| {
                     set_form_interval_ms.set(event_target_value(&event));
                 }
@@ -644,22 +1078,31 @@ fn RealtimeKeys() -> impl IntoView {
 
 
              {
-                move || match ron::from_str::<Option<u32>>(form_interval_ms.get().as_str()) {
-                    Ok(_) => view! {
-                        <p>String::from("✅ Interval is valid")</p>
+                move || match FetchInterval::parse(form_interval_ms.get().as_str()) {
+                    Ok(FetchInterval::Default) => view! {
+                        <p>{t!(i18n, interval_valid_default, interval = move || format_ms(default_interval_ms.get()))}</p>
+                    }.into_any(),
+                    Ok(FetchInterval::Every { ms }) => view! {
+                        <p>{t!(i18n, interval_valid, interval = format_ms(ms))}</p>
+                    }.into_any(),
+                    Err(IntervalError::Unrecognised) => view! {
+                        <p>{t!(i18n, interval_invalid)}</p>
+                    }.into_any(),
+                    Err(IntervalError::TooShort) => view! {
+                        <p>{t!(i18n, interval_too_short, min = format_ms(MIN_FETCH_INTERVAL_MS))}</p>
+                    }.into_any(),
+                    Err(IntervalError::TooLong) => view! {
+                        <p>{t!(i18n, interval_too_long, max = format_ms(MAX_FETCH_INTERVAL_MS))}</p>
                     }.into_any(),
-                    Err(_) => view! {
-                        <p>String::from("❌ Interval is invalid, must be Option<u32> like Some(1000) or None")</p>
-                }.into_any()
             }
              }
 
-            <p>"password"</p>
+            <p>{t!(i18n, form_password)}</p>
 
             <textarea
 
                 prop:value=move || form_password.get()
-                disabled=move || !authorised.get()
+                disabled=move || !can_edit()
                 class= "w-full bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-base h-[400px]"
                 on:input=move |event| {
                     set_form_password.set(event_target_value(&event));
@@ -677,56 +1120,74 @@ fn RealtimeKeys() -> impl IntoView {
                                 let passwords = formatted_password.passwords.iter().map(|x| x.password.len()).collect::<Vec<_>>();
                                 let all_same = passwords.iter().all(|x| *x == key_formats);
 
-                                match all_same {
-                                    true => view! {
-                                     <p>"✅ Password is valid"</p>
+                                match (all_same, crate::rotation::validate_rotation(&formatted_password)) {
+                                    (true, Ok(())) => view! {
+                                     <p>{t!(i18n, password_valid)}</p>
                                     }.into_any(),
-                                    false => view! {
-                                        <p>"❌ Password is invalid, must be the same length as key format"</p>
+                                    (true, Err(reason)) => view! {
+                                        <p>{t!(i18n, password_invalid_rotation, reason = crate::rotation::describe_rotation_error(i18n, reason))}</p>
+                                    }.into_any(),
+                                    (false, _) => view! {
+                                        <p>{t!(i18n, password_invalid_length)}</p>
                                     }.into_any()
                                 }
                             },
                             None => {
                                 view! {
-                                    <><p>"✅ Password is valid"</p></>
+                                    <><p>{t!(i18n, password_valid)}</p></>
                                 }.into_any()
                             }
                         }
                     },
                     Err(err) => view! {
 
-                        <p>"❌ Password is invalid"</p>
+                        <p>{t!(i18n, password_invalid)}</p>
                         <p class="font-mono">{format!("{:#?}", err)}</p>
 
                 }.into_any()
             }
             }
 
+            <RotationPanel master_email=session_email master_password=session_password form_feed_id form_password set_form_password/>
+
             <button
 
                 class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold"
-                disabled=move || !authorised.get()
+                disabled=move || !can_edit()
             on:click=move |e| {
-              let master_email = master_email.get();
-                let master_password = master_password.get();
+              let master_email = session_email.get();
+                let master_password = session_password.get();
               let (form_feed_id, form_password, form_interval_ms) = (form_feed_id.get(),
               form_password.get(),
               form_interval_ms.get());
 
               spawn_local(async move {
-                submit_data(master_email, master_password, form_feed_id, form_password, form_interval_ms).await;
+                set_submit_message.set(match submit_data(master_email, master_password, form_feed_id, form_password, form_interval_ms).await {
+                    Ok(SubmitOutcome::Applied) => t_string!(i18n, submitted).to_string(),
+                    Ok(SubmitOutcome::PendingApproval(id)) => t_string!(i18n, pending_approval, id = id).to_string(),
+                    Ok(SubmitOutcome::Unauthorised) => format!("❌ {}", t_string!(i18n, not_authorised)),
+                    Err(err) => format!("❌ {}", err),
+                });
                 set_count.set(count.get() + 1);
                 //async_data_load.refetch();
               });
             }
 
 
-                >"Submit"</button>
+                >{t!(i18n, submit)}</button>
+                <p>{move || submit_message.get()}</p>
+                        }.into_any()
+                    } else {
+                        view! {
+                            <p>{t!(i18n, viewer_cannot_edit)}</p>
+                        }.into_any()
+                    }
+                }
                         }.into_any()
                     } else {
                         view! {
 
-                            <p>"Not authorised"</p>
+                            <p>{t!(i18n, not_authorised)}</p>
 
                         }.into_any()
                     }
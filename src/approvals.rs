@@ -0,0 +1,486 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Two-person approval for production feeds.
+// Submitting keys for a feed listed in approval_required_feeds creates a change request instead,
+// which a second Key Editor has to approve before Tulip sends it to Birch.
+
+use crate::app::{EachPasswordRow, Nav};
+use crate::diff::LineDiff;
+use crate::i18n::*;
+use crate::page_meta::PageMeta;
+use crate::time::Time;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use leptos_i18n::I18nContext;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Clone, Copy, Deserialize, Debug, PartialEq, Eq)]
+pub enum ChangeStatus {
+    Pending,
+    /// Approved and being sent to Birch.
+    Applying,
+    Approved,
+    Rejected,
+}
+
+#[derive(Serialize, Clone, Deserialize, Debug)]
+pub struct ChangeComment {
+    pub author: String,
+    pub time_ms: i64,
+    pub text: String,
+}
+
+#[derive(Serialize, Clone, Deserialize, Debug)]
+pub struct ChangeRequest {
+    pub id: u64,
+    pub feed_id: String,
+    pub author: String,
+    pub created_ms: i64,
+    pub status: ChangeStatus,
+    pub reviewer: Option<String>,
+    /// The keys to submit. Dropped once the request is resolved so secrets do not linger here.
+    pub row: Option<EachPasswordRow>,
+    /// Fingerprinted RON of `row`, kept after it is dropped.
+    pub after_text: String,
+    pub comments: Vec<ChangeComment>,
+}
+
+/// A change request as sent to the browser, with secrets fingerprinted.
+#[derive(Serialize, Clone, Deserialize, Debug)]
+pub struct ChangeRequestSummary {
+    pub id: u64,
+    pub feed_id: String,
+    pub author: String,
+    pub created_ms: i64,
+    pub status: ChangeStatus,
+    pub reviewer: Option<String>,
+    /// What Birch has for the feed now.
+    pub before_text: String,
+    pub after_text: String,
+    pub comments: Vec<ChangeComment>,
+}
+
+#[cfg(feature = "ssr")]
+static CHANGES_FILE_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+#[cfg(feature = "ssr")]
+fn read_changes(path: &str) -> Result<BTreeMap<u64, ChangeRequest>, ServerFnError> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => Ok(ron::from_str(&text)?),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Applies `change` to the stored change requests and saves them, holding the lock throughout.
+#[cfg(feature = "ssr")]
+fn modify_changes<T>(
+    change: impl FnOnce(&mut BTreeMap<u64, ChangeRequest>) -> Result<T, ServerFnError>,
+) -> Result<T, ServerFnError> {
+    let path = &crate::config::config().change_requests_file;
+    let _guard = CHANGES_FILE_LOCK.lock().unwrap();
+
+    let mut changes = read_changes(path)?;
+    let result = change(&mut changes)?;
+
+    //write then rename so a crash never leaves a half written store
+    let temporary_path = format!("{}.tmp", path);
+    std::fs::write(
+        &temporary_path,
+        ron::ser::to_string_pretty(&changes, ron::ser::PrettyConfig::default())?,
+    )?;
+    std::fs::rename(&temporary_path, path)?;
+
+    Ok(result)
+}
+
+#[cfg(feature = "ssr")]
+pub fn requires_approval(feed_id: &str) -> bool {
+    crate::config::config()
+        .approval_required_feeds
+        .iter()
+        .any(|x| x == feed_id || x == "*")
+}
+
+/// Stores a change for review and returns its id.
+#[cfg(feature = "ssr")]
+pub fn create_change_request(
+    feed_id: &str,
+    author: &str,
+    row: &EachPasswordRow,
+) -> Result<u64, ServerFnError> {
+    modify_changes(|changes| {
+        let id = changes.keys().next_back().map(|x| x + 1).unwrap_or(1);
+
+        changes.insert(
+            id,
+            ChangeRequest {
+                id,
+                feed_id: feed_id.to_string(),
+                author: author.to_string(),
+                created_ms: chrono::Utc::now().timestamp_millis(),
+                status: ChangeStatus::Pending,
+                reviewer: None,
+                row: Some(row.clone()),
+                after_text: row.audit_text(),
+                comments: vec![],
+            },
+        );
+
+        Ok(id)
+    })
+}
+
+#[server(endpoint = "list_change_requests")]
+pub async fn list_change_requests(
+    master_email: String,
+    master_password: String,
+) -> Result<Option<Vec<ChangeRequestSummary>>, ServerFnError> {
+    let mut operator = match crate::auth::authenticate_verified(&master_email, &master_password).await? {
+        Some(operator) => operator,
+        None => return Ok(None),
+    };
+
+    crate::auth::require_role(&operator, crate::auth::Role::KeyEditor)?;
+
+    let current_keys = match operator.keys().await? {
+        Some(key_response) => key_response.passwords,
+        None => return Ok(None),
+    };
+
+    let changes = {
+        let _guard = CHANGES_FILE_LOCK.lock().unwrap();
+        read_changes(&crate::config::config().change_requests_file)?
+    };
+
+    Ok(Some(
+        changes
+            .into_values()
+            .rev()
+            .map(|change| ChangeRequestSummary {
+                before_text: current_keys
+                    .get(&change.feed_id)
+                    .map(|x| x.audit_text())
+                    .unwrap_or_default(),
+                id: change.id,
+                feed_id: change.feed_id,
+                author: change.author,
+                created_ms: change.created_ms,
+                status: change.status,
+                reviewer: change.reviewer,
+                after_text: change.after_text,
+                comments: change.comments,
+            })
+            .collect(),
+    ))
+}
+
+#[cfg(feature = "ssr")]
+async fn review_change_request_checked(
+    entry: &mut crate::audit::AuditEntry,
+    master_email: &str,
+    master_password: &str,
+    id: u64,
+    approve: bool,
+    comment: &str,
+) -> Result<bool, ServerFnError> {
+    let mut operator = match crate::auth::authenticate_verified(master_email, master_password).await? {
+        Some(operator) => operator,
+        None => return Ok(false),
+    };
+
+    crate::auth::require_role(&operator, crate::auth::Role::KeyEditor)?;
+
+    let comment = comment.trim();
+    let now_ms = chrono::Utc::now().timestamp_millis();
+
+    //claim the request under the lock, so a second reviewer finds it already taken
+    let claimed = modify_changes(|changes| {
+        let change = match changes.get_mut(&id) {
+            Some(change) => change,
+            None => return Err(ServerFnError::new(format!("No change request {}", id))),
+        };
+
+        if change.status != ChangeStatus::Pending {
+            return Err(ServerFnError::new(format!("Change request {} was already reviewed", id)));
+        }
+
+        if same_email(&change.author, &operator.email) {
+            return Err(ServerFnError::new("Changes must be reviewed by someone other than their author"));
+        }
+
+        change.status = match approve {
+            true => ChangeStatus::Applying,
+            false => ChangeStatus::Rejected,
+        };
+        change.reviewer = Some(operator.email.clone());
+
+        if !approve {
+            change.row = None;
+        }
+
+        if !comment.is_empty() {
+            change.comments.push(ChangeComment {
+                author: operator.email.clone(),
+                time_ms: now_ms,
+                text: comment.to_string(),
+            });
+        }
+
+        Ok(change.clone())
+    })?;
+
+    entry.feed_id = Some(claimed.feed_id.clone());
+
+    if !approve {
+        return Ok(true);
+    }
+
+    let applied = match &claimed.row {
+        Some(row) => {
+            let note = format!("Change request {} by {}, approved by {}", id, claimed.author, operator.email);
+            crate::app::apply_key_change(&mut operator, entry, &claimed.feed_id, row, &note).await
+        }
+        None => Err(ServerFnError::new(format!("Change request {} has no keys", id))),
+    };
+
+    //only a change Birch accepted is approved, otherwise it goes back to pending for another try
+    let succeeded = matches!(applied, Ok(true));
+
+    modify_changes(|changes| {
+        if let Some(change) = changes.get_mut(&id) {
+            if succeeded {
+                change.status = ChangeStatus::Approved;
+                change.row = None;
+            } else {
+                change.status = ChangeStatus::Pending;
+                change.reviewer = None;
+            }
+        }
+
+        Ok(())
+    })?;
+
+    applied
+}
+
+/// Emails are compared the way operators type them, ignoring case and surrounding spaces.
+#[cfg(feature = "ssr")]
+fn same_email(a: &str, b: &str) -> bool {
+    a.trim().to_lowercase() == b.trim().to_lowercase()
+}
+
+#[server(endpoint = "review_change_request")]
+pub async fn review_change_request(
+    master_email: String,
+    master_password: String,
+    id: u64,
+    approve: bool,
+    comment: String,
+) -> Result<bool, ServerFnError> {
+    use crate::audit::{AuditAction, AuditEntry, AuditOutcome};
+
+    let action = match approve {
+        true => AuditAction::ApproveChange,
+        false => AuditAction::RejectChange,
+    };
+
+    let mut entry = AuditEntry::new(&master_email, action, None);
+    let result = review_change_request_checked(&mut entry, &master_email, &master_password, id, approve, &comment).await;
+    entry.outcome = AuditOutcome::from_bool(&result);
+    crate::audit::record(entry)?;
+
+    result
+}
+
+#[server(endpoint = "comment_change_request")]
+pub async fn comment_change_request(
+    master_email: String,
+    master_password: String,
+    id: u64,
+    comment: String,
+) -> Result<bool, ServerFnError> {
+    let operator = match crate::auth::authenticate_verified(&master_email, &master_password).await? {
+        Some(operator) => operator,
+        None => return Ok(false),
+    };
+
+    crate::auth::require_role(&operator, crate::auth::Role::KeyEditor)?;
+
+    if comment.trim().is_empty() {
+        return Err(ServerFnError::new("Comment cannot be empty"));
+    }
+
+    modify_changes(|changes| match changes.get_mut(&id) {
+        Some(change) => {
+            change.comments.push(ChangeComment {
+                author: operator.email.clone(),
+                time_ms: chrono::Utc::now().timestamp_millis(),
+                text: comment.trim().to_string(),
+            });
+            Ok(())
+        }
+        None => Err(ServerFnError::new(format!("No change request {}", id))),
+    })?;
+
+    Ok(true)
+}
+
+fn status_label(i18n: I18nContext<Locale>, status: ChangeStatus) -> &'static str {
+    match status {
+        ChangeStatus::Pending => t_string!(i18n, approvals_status_pending),
+        ChangeStatus::Applying => t_string!(i18n, approvals_status_applying),
+        ChangeStatus::Approved => t_string!(i18n, approvals_status_approved),
+        ChangeStatus::Rejected => t_string!(i18n, approvals_status_rejected),
+    }
+}
+
+#[component]
+pub fn Approvals() -> impl IntoView {
+    let i18n = use_i18n();
+
+    let (master_email, set_master_email) = signal(String::from(""));
+    let (master_password, set_master_password) = signal(String::from(""));
+
+    let changes: RwSignal<Option<Vec<ChangeRequestSummary>>> = RwSignal::new(None);
+    let (message, set_message) = signal(String::from(""));
+
+    // comment box contents, keyed by change request id
+    let comments: RwSignal<BTreeMap<u64, String>> = RwSignal::new(BTreeMap::new());
+
+    let reload = move || {
+        let (master_email, master_password) = (master_email.get(), master_password.get());
+
+        spawn_local(async move {
+            match list_change_requests(master_email, master_password).await {
+                Ok(Some(list)) => changes.set(Some(list)),
+                Ok(None) => set_message.set(t_string!(i18n, not_authorised).to_string()),
+                Err(err) => set_message.set(err.to_string()),
+            }
+        });
+    };
+
+    let show_result = move |result: Result<bool, ServerFnError>, success: &str| match result {
+        Ok(true) => {
+            set_message.set(success.to_string());
+            reload();
+        }
+        Ok(false) => set_message.set(t_string!(i18n, not_authorised).to_string()),
+        Err(err) => set_message.set(err.to_string()),
+    };
+
+    view! {
+        <PageMeta title=move || t_string!(i18n, nav_approvals) noindex=true/>
+        <Nav/>
+        <main class="p-8">
+            <h1 class="text-2xl font-bold text-tulip">{t!(i18n, nav_approvals)}</h1>
+            <p>{t!(i18n, approvals_intro)}</p>
+
+            <form on:submit=move |event| {
+                event.prevent_default();
+                reload();
+            }>
+                <input
+                    type="email"
+                    placeholder=move || t_string!(i18n, email)
+                    prop:value=move || master_email.get()
+                    class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold mr-4"
+                    on:input=move |event| set_master_email.set(event_target_value(&event))
+                />
+                <input
+                    type="password"
+                    autocomplete="current-password"
+                    placeholder=move || t_string!(i18n, password)
+                    prop:value=move || master_password.get()
+                    class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold mr-4"
+                    on:input=move |event| set_master_password.set(event_target_value(&event))
+                />
+                <button type="submit" class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold">{t!(i18n, load)}</button>
+            </form>
+
+            <p class="font-semibold">{move || message.get()}</p>
+
+            {
+                move || changes.get().map(|list| view! {
+                    <ul class="my-4 space-y-8">
+                        {
+                            list.into_iter().map(|change| {
+                                let id = change.id;
+                                let pending = change.status == ChangeStatus::Pending;
+                                let comment_text = move || comments.with(|x| x.get(&id).cloned().unwrap_or_default());
+
+                                view! {
+                                    <li>
+                                        <h2 class="text-lg font-semibold">
+                                            {
+                                                let (feed_id, author, status) = (change.feed_id.clone(), change.author.clone(), change.status);
+                                                move || t_string!(i18n, approvals_heading, id = id, feed_id = feed_id.clone(), author = author.clone(), status = status_label(i18n, status)).to_string()
+                                            }
+                                        </h2>
+                                        <p><Time time_ms=change.created_ms feed_id=change.feed_id.clone()/></p>
+                                        {change.reviewer.clone().map(|reviewer| view! { <p>{move || t_string!(i18n, approvals_reviewed_by, reviewer = reviewer.clone()).to_string()}</p> })}
+                                        {pending.then(|| view! {
+                                            <LineDiff before=change.before_text.clone() after=change.after_text.clone()/>
+                                        })}
+
+                                        <ul class="my-2">
+                                            {
+                                                change.comments.iter().map(|comment| view! {
+                                                    <li>
+                                                        <span class="font-semibold mr-2">{comment.author.clone()}</span>
+                                                        <span class="mr-2"><Time time_ms=comment.time_ms feed_id=change.feed_id.clone()/></span>
+                                                        <span>{comment.text.clone()}</span>
+                                                    </li>
+                                                }).collect_view()
+                                            }
+                                        </ul>
+
+                                        <textarea
+                                            placeholder=move || t_string!(i18n, approvals_comment_placeholder)
+                                            prop:value=comment_text
+                                            class="w-full bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-2 text-base"
+                                            on:input=move |event| comments.update(|x| { x.insert(id, event_target_value(&event)); })
+                                        />
+
+                                        <div class="flex flex-row gap-x-2">
+                                            <button class="bg-gray dark:bg-darksky rounded-md p-1 px-2 border-2 border-tulip text-sm font-bold"
+                                            on:click=move |_| {
+                                                let (master_email, master_password, comment) = (master_email.get(), master_password.get(), comment_text());
+                                                spawn_local(async move {
+                                                    show_result(comment_change_request(master_email, master_password, id, comment).await, t_string!(i18n, approvals_comment_added));
+                                                    comments.update(|x| { x.remove(&id); });
+                                                });
+                                            }
+                                            >{t!(i18n, approvals_comment)}</button>
+                                            {pending.then(|| view! {
+                                                <button class="bg-gray dark:bg-darksky rounded-md p-1 px-2 border-2 border-tulip text-sm font-bold"
+                                                on:click=move |_| {
+                                                    let (master_email, master_password, comment) = (master_email.get(), master_password.get(), comment_text());
+                                                    spawn_local(async move {
+                                                        show_result(review_change_request(master_email, master_password, id, true, comment).await, t_string!(i18n, approvals_approved));
+                                                    });
+                                                }
+                                                >{t!(i18n, approvals_approve)}</button>
+                                                <button class="bg-gray dark:bg-darksky rounded-md p-1 px-2 border-2 border-tulip text-sm font-bold"
+                                                on:click=move |_| {
+                                                    let (master_email, master_password, comment) = (master_email.get(), master_password.get(), comment_text());
+                                                    spawn_local(async move {
+                                                        show_result(review_change_request(master_email, master_password, id, false, comment).await, t_string!(i18n, approvals_rejected));
+                                                    });
+                                                }
+                                                >{t!(i18n, approvals_reject)}</button>
+                                            })}
+                                        </div>
+                                    </li>
+                                }
+                            }).collect_view()
+                        }
+                    </ul>
+                })
+            }
+        </main>
+    }
+}
@@ -0,0 +1,421 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Append-only record of who touched which keys, required by agencies' API key terms.
+// Entries are JSON lines in the file set as audit_log_file in tulip.ron.
+
+use crate::app::Nav;
+use crate::diff::LineDiff;
+use crate::i18n::*;
+use crate::page_meta::PageMeta;
+use crate::time::Time;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use leptos_i18n::I18nContext;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Clone, Copy, Deserialize, Debug, PartialEq, Eq)]
+pub enum AuditAction {
+    /// `load_realtime_keys`, which is how operators log in to the key manager.
+    Login,
+    RevealKey,
+    SubmitKeys,
+    RestoreKeys,
+    ProbeKey,
+    /// A submission to a feed needing two-person approval.
+    RequestChange,
+    ApproveChange,
+    RejectChange,
+    AddUser,
+    SetUserDisabled,
+    ChangePassword,
+}
+
+impl AuditAction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AuditAction::Login => "Login",
+            AuditAction::RevealKey => "Reveal key",
+            AuditAction::SubmitKeys => "Submit keys",
+            AuditAction::RestoreKeys => "Restore keys",
+            AuditAction::ProbeKey => "Probe key",
+            AuditAction::RequestChange => "Request change",
+            AuditAction::ApproveChange => "Approve change",
+            AuditAction::RejectChange => "Reject change",
+            AuditAction::AddUser => "Add user",
+            AuditAction::SetUserDisabled => "Enable/disable user",
+            AuditAction::ChangePassword => "Change password",
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Deserialize, Debug, PartialEq, Eq)]
+pub enum AuditOutcome {
+    Success,
+    RejectedCredentials,
+    Failed(String),
+}
+
+impl AuditOutcome {
+    pub fn from_option<T>(result: &Result<Option<T>, ServerFnError>) -> AuditOutcome {
+        match result {
+            Ok(Some(_)) => AuditOutcome::Success,
+            Ok(None) => AuditOutcome::RejectedCredentials,
+            Err(err) => AuditOutcome::Failed(err.to_string()),
+        }
+    }
+
+    pub fn from_bool(result: &Result<bool, ServerFnError>) -> AuditOutcome {
+        match result {
+            Ok(true) => AuditOutcome::Success,
+            Ok(false) => AuditOutcome::RejectedCredentials,
+            Err(err) => AuditOutcome::Failed(err.to_string()),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            AuditOutcome::Success => String::from("Success"),
+            AuditOutcome::RejectedCredentials => String::from("Rejected credentials"),
+            AuditOutcome::Failed(reason) => format!("Failed, {}", reason),
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Deserialize, Debug)]
+pub struct AuditEntry {
+    /// Milliseconds since the unix epoch, UTC.
+    pub time_ms: i64,
+    /// The email the operator logged in with.
+    pub actor: String,
+    pub action: AuditAction,
+    pub feed_id: Option<String>,
+    pub outcome: AuditOutcome,
+    /// RON of the feed's keys before and after, with secret values fingerprinted.
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+impl AuditEntry {
+    pub fn new(actor: &str, action: AuditAction, feed_id: Option<String>) -> AuditEntry {
+        AuditEntry {
+            time_ms: chrono::Utc::now().timestamp_millis(),
+            actor: actor.trim().to_string(),
+            action,
+            feed_id,
+            outcome: AuditOutcome::Success,
+            before: None,
+            after: None,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Deserialize, Debug, Default)]
+pub struct AuditFilter {
+    pub actor: String,
+    pub feed_id: String,
+    /// Inclusive dates as YYYY-MM-DD, in UTC. Empty means unbounded.
+    pub from_date: String,
+    pub to_date: String,
+}
+
+impl AuditFilter {
+    #[cfg(feature = "ssr")]
+    pub fn matches(&self, entry: &AuditEntry) -> Result<bool, ServerFnError> {
+        let date = match chrono::DateTime::from_timestamp_millis(entry.time_ms) {
+            Some(time) => time.date_naive(),
+            None => return Ok(false),
+        };
+
+        let parse_date = |date: &str| chrono::NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d");
+
+        if !self.actor.trim().is_empty() && !entry.actor.contains(self.actor.trim()) {
+            return Ok(false);
+        }
+
+        if !self.feed_id.trim().is_empty() && entry.feed_id.as_deref() != Some(self.feed_id.trim()) {
+            return Ok(false);
+        }
+
+        if !self.from_date.trim().is_empty() && date < parse_date(&self.from_date)? {
+            return Ok(false);
+        }
+
+        if !self.to_date.trim().is_empty() && date > parse_date(&self.to_date)? {
+            return Ok(false);
+        }
+
+        Ok(true)
+    }
+}
+
+#[cfg(feature = "ssr")]
+static AUDIT_FILE_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+/// Appends an entry to the audit log.
+/// Callers propagate the error, an action that cannot be audited should not appear to succeed.
+#[cfg(feature = "ssr")]
+pub fn record(entry: AuditEntry) -> Result<(), ServerFnError> {
+    use std::io::Write;
+
+    let path = &crate::config::config().audit_log_file;
+    let line = serde_json::to_string(&entry)?;
+
+    let _guard = AUDIT_FILE_LOCK.lock().unwrap();
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(file, "{}", line)?;
+
+    Ok(())
+}
+
+#[cfg(feature = "ssr")]
+fn read_entries(filter: &AuditFilter) -> Result<Vec<AuditEntry>, ServerFnError> {
+    let path = &crate::config::config().audit_log_file;
+
+    let text = {
+        let _guard = AUDIT_FILE_LOCK.lock().unwrap();
+        match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err.into()),
+        }
+    };
+
+    let mut entries = vec![];
+
+    for line in text.lines().filter(|x| !x.trim().is_empty()) {
+        let entry: AuditEntry = serde_json::from_str(line)?;
+
+        if filter.matches(&entry)? {
+            entries.push(entry);
+        }
+    }
+
+    //newest first
+    entries.reverse();
+
+    Ok(entries)
+}
+
+#[server(endpoint = "load_audit_log")]
+pub async fn load_audit_log(
+    master_email: String,
+    master_password: String,
+    filter: AuditFilter,
+) -> Result<Option<Vec<AuditEntry>>, ServerFnError> {
+    let operator = match crate::auth::authenticate_verified(&master_email, &master_password).await? {
+        Some(operator) => operator,
+        None => return Ok(None),
+    };
+
+    crate::auth::require_role(&operator, crate::auth::Role::Admin)?;
+
+    Ok(Some(read_entries(&filter)?))
+}
+
+/// `AuditAction::name` stays English for the CSV export, this is what the page shows.
+fn action_label(i18n: I18nContext<Locale>, action: AuditAction) -> &'static str {
+    match action {
+        AuditAction::Login => t_string!(i18n, audit_action_login),
+        AuditAction::RevealKey => t_string!(i18n, audit_action_reveal_key),
+        AuditAction::SubmitKeys => t_string!(i18n, audit_action_submit_keys),
+        AuditAction::RestoreKeys => t_string!(i18n, audit_action_restore_keys),
+        AuditAction::ProbeKey => t_string!(i18n, audit_action_probe_key),
+        AuditAction::RequestChange => t_string!(i18n, audit_action_request_change),
+        AuditAction::ApproveChange => t_string!(i18n, audit_action_approve_change),
+        AuditAction::RejectChange => t_string!(i18n, audit_action_reject_change),
+        AuditAction::AddUser => t_string!(i18n, audit_action_add_user),
+        AuditAction::SetUserDisabled => t_string!(i18n, audit_action_set_user_disabled),
+        AuditAction::ChangePassword => t_string!(i18n, audit_action_change_password),
+    }
+}
+
+fn outcome_label(i18n: I18nContext<Locale>, outcome: &AuditOutcome) -> String {
+    match outcome {
+        AuditOutcome::Success => t_string!(i18n, audit_outcome_success).to_string(),
+        AuditOutcome::RejectedCredentials => t_string!(i18n, audit_outcome_rejected).to_string(),
+        AuditOutcome::Failed(reason) => t_string!(i18n, audit_outcome_failed, reason = reason).to_string(),
+    }
+}
+
+pub fn format_time_ms(time_ms: i64) -> String {
+    match chrono::DateTime::from_timestamp_millis(time_ms) {
+        Some(time) => time.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+        None => String::from("Unknown time"),
+    }
+}
+
+fn csv_field(field: &str) -> String {
+    // spreadsheets run a cell starting with one of these as a formula, the quote keeps it text
+    let field = match field.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        true => format!("'{}", field),
+        false => field.to_string(),
+    };
+
+    match field.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field,
+    }
+}
+
+pub fn entries_to_csv(entries: &[AuditEntry]) -> String {
+    let mut csv = String::from("time,actor,action,feed_id,outcome,before,after\n");
+
+    for entry in entries {
+        let fields = [
+            format_time_ms(entry.time_ms),
+            entry.actor.clone(),
+            entry.action.name().to_string(),
+            entry.feed_id.clone().unwrap_or_default(),
+            entry.outcome.describe(),
+            entry.before.clone().unwrap_or_default(),
+            entry.after.clone().unwrap_or_default(),
+        ];
+
+        csv.push_str(&fields.iter().map(|x| csv_field(x)).collect::<Vec<_>>().join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
+
+/// Percent encodes everything but unreserved characters, for use in a data: URL.
+fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+#[component]
+pub fn Audit() -> impl IntoView {
+    let i18n = use_i18n();
+
+    let (master_email, set_master_email) = signal(String::from(""));
+    let (master_password, set_master_password) = signal(String::from(""));
+
+    let filter: RwSignal<AuditFilter> = RwSignal::new(AuditFilter::default());
+    let entries: RwSignal<Option<Vec<AuditEntry>>> = RwSignal::new(None);
+    let (message, set_message) = signal(String::from(""));
+
+    let search = move || {
+        let (master_email, master_password, filter) = (master_email.get(), master_password.get(), filter.get());
+
+        spawn_local(async move {
+            match load_audit_log(master_email, master_password, filter).await {
+                Ok(Some(list)) => {
+                    set_message.set(t_string!(i18n, audit_entries, count = list.len()).to_string());
+                    entries.set(Some(list));
+                }
+                Ok(None) => set_message.set(t_string!(i18n, not_authorised).to_string()),
+                Err(err) => set_message.set(err.to_string()),
+            }
+        });
+    };
+
+    view! {
+        <PageMeta title=move || t_string!(i18n, nav_audit) noindex=true/>
+        <Nav/>
+        <main class="p-8">
+            <h1 class="text-2xl font-bold text-tulip">{t!(i18n, nav_audit)}</h1>
+            <p>{t!(i18n, audit_intro)}</p>
+
+            <form on:submit=move |event| {
+                event.prevent_default();
+                search();
+            }>
+                <input
+                    type="email"
+                    placeholder=move || t_string!(i18n, email)
+                    prop:value=move || master_email.get()
+                    class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold mr-4"
+                    on:input=move |event| set_master_email.set(event_target_value(&event))
+                />
+                <input
+                    type="password"
+                    autocomplete="current-password"
+                    placeholder=move || t_string!(i18n, password)
+                    prop:value=move || master_password.get()
+                    class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold mr-4"
+                    on:input=move |event| set_master_password.set(event_target_value(&event))
+                />
+                <br/>
+                <input
+                    type="text"
+                    placeholder=move || t_string!(i18n, audit_user)
+                    prop:value=move || filter.with(|x| x.actor.clone())
+                    class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 mr-4"
+                    on:input=move |event| filter.update(|x| x.actor = event_target_value(&event))
+                />
+                <input
+                    type="text"
+                    placeholder=move || t_string!(i18n, feed_id)
+                    prop:value=move || filter.with(|x| x.feed_id.clone())
+                    class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 mr-4"
+                    on:input=move |event| filter.update(|x| x.feed_id = event_target_value(&event))
+                />
+                <label class="mr-2">{t!(i18n, audit_from)}</label>
+                <input
+                    type="date"
+                    prop:value=move || filter.with(|x| x.from_date.clone())
+                    class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 mr-4"
+                    on:input=move |event| filter.update(|x| x.from_date = event_target_value(&event))
+                />
+                <label class="mr-2">{t!(i18n, audit_to)}</label>
+                <input
+                    type="date"
+                    prop:value=move || filter.with(|x| x.to_date.clone())
+                    class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 mr-4"
+                    on:input=move |event| filter.update(|x| x.to_date = event_target_value(&event))
+                />
+                <button type="submit" class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold">{t!(i18n, audit_search)}</button>
+            </form>
+
+            <p class="font-semibold">{move || message.get()}</p>
+
+            {
+                move || entries.get().map(|list| view! {
+                    <a
+                        class="underline text-blue-500 dark:text-blue-300"
+                        download="tulip-audit.csv"
+                        href={format!("data:text/csv;charset=utf-8,{}", percent_encode(&entries_to_csv(&list)))}
+                    >{t!(i18n, audit_export_csv)}</a>
+
+                    <ul class="my-4 space-y-4">
+                        {
+                            list.into_iter().map(|entry| view! {
+                                <li>
+                                    <p>
+                                        <span class="mr-4"><Time time_ms=entry.time_ms feed_id=entry.feed_id.clone()/></span>
+                                        <span class="font-semibold mr-4">{entry.actor.clone()}</span>
+                                        <span class="mr-4">{move || action_label(i18n, entry.action)}</span>
+                                        <span class="font-mono mr-4">{entry.feed_id.clone().unwrap_or_default()}</span>
+                                        <span>{
+                                            let outcome = entry.outcome.clone();
+                                            move || outcome_label(i18n, &outcome)
+                                        }</span>
+                                    </p>
+                                    {
+                                        match (entry.before.clone(), entry.after.clone()) {
+                                            (None, None) => None,
+                                            (before, after) => Some(view! {
+                                                <LineDiff before={before.unwrap_or_default()} after={after.unwrap_or_default()}/>
+                                            }),
+                                        }
+                                    }
+                                </li>
+                            }).collect_view()
+                        }
+                    </ul>
+                })
+            }
+        </main>
+    }
+}
@@ -0,0 +1,189 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+use serde::{Deserialize, Serialize};
+
+/// What an operator is allowed to do in Tulip.
+/// Ordered from least to most privileged, so roles can be compared with `>=`.
+#[derive(Serialize, Clone, Copy, Deserialize, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Role {
+    /// Can see feed health and GTFS-RT views, never secret values.
+    Viewer,
+    /// Can reveal and change realtime keys.
+    KeyEditor,
+    /// Everything, including managing other operators.
+    Admin,
+}
+
+impl Role {
+    pub fn can_reveal_secrets(&self) -> bool {
+        *self >= Role::KeyEditor
+    }
+
+    pub fn can_edit_keys(&self) -> bool {
+        *self >= Role::KeyEditor
+    }
+
+    pub fn is_admin(&self) -> bool {
+        *self == Role::Admin
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Role::Viewer => "Viewer",
+            Role::KeyEditor => "Key Editor",
+            Role::Admin => "Admin",
+        }
+    }
+}
+
+/// Someone who has logged into Tulip, and the credentials Tulip uses on their behalf with Birch.
+#[cfg(feature = "ssr")]
+#[derive(Debug)]
+pub struct Operator {
+    pub email: String,
+    pub role: Role,
+    /// Whether this is a Tulip account from the user store rather than a Birch login.
+    pub local: bool,
+    pub birch_email: String,
+    pub birch_password: String,
+    /// What Birch answered when it confirmed a Birch login, so the request does not fetch the keys again.
+    verified_keys: Option<crate::app::KeyResponse>,
+}
+
+#[cfg(feature = "ssr")]
+impl Operator {
+    /// Every feed's keys, reusing the ones fetched to confirm the login. `Ok(None)` means Birch rejected the credentials.
+    pub async fn keys(&mut self) -> Result<Option<crate::app::KeyResponse>, leptos::prelude::ServerFnError> {
+        match self.verified_keys.take() {
+            Some(key_response) => Ok(Some(key_response)),
+            None => crate::birch::fetch_keys(&self.birch_email, &self.birch_password).await,
+        }
+    }
+}
+
+/// Looks up the role Tulip grants to a Birch login.
+/// This does not authenticate them, Birch still has to accept their credentials.
+#[cfg(feature = "ssr")]
+pub fn role_for(email: &str) -> Role {
+    let config = crate::config::config();
+
+    config
+        .roles
+        .get(email.trim())
+        .copied()
+        .unwrap_or(config.default_role)
+}
+
+/// Works out who is making a request.
+///
+/// Once the user store has accounts in it, only those accounts can log in and Tulip talks to Birch
+/// with its own service credentials. Otherwise the credentials are passed through to Birch,
+/// so a `Some` here is not yet proof they are valid, `authenticate_verified` checks them.
+#[cfg(feature = "ssr")]
+fn authenticate(
+    email: &str,
+    password: &str,
+) -> Result<Option<Operator>, leptos::prelude::ServerFnError> {
+    let email = email.trim();
+
+    if let Err(err) = crate::rate_limit::check_account(email) {
+        crate::metrics::record_login("locked_out");
+        return Err(err);
+    }
+
+    if crate::users::local_accounts_enabled()? {
+        let config = crate::config::config();
+
+        let account = match crate::users::check_login(email, password)? {
+            Some(account) => account,
+            None => {
+                println!("Failed local login for {}", email);
+                crate::rate_limit::record_failed_login(email);
+                crate::metrics::record_login("failure");
+                return Ok(None);
+            }
+        };
+
+        crate::rate_limit::record_successful_login(email);
+        crate::metrics::record_login("success");
+
+        let (birch_email, birch_password) = match (&config.birch_email, &config.birch_password) {
+            (Some(birch_email), Some(birch_password)) => (birch_email.clone(), birch_password.clone()),
+            _ => {
+                return Err(leptos::prelude::ServerFnError::new(
+                    "Local accounts are enabled but birch_email and birch_password are not configured",
+                ));
+            }
+        };
+
+        return Ok(Some(Operator {
+            email: account.email,
+            role: account.role,
+            local: true,
+            birch_email,
+            birch_password,
+            verified_keys: None,
+        }));
+    }
+
+    Ok(Some(Operator {
+        email: email.to_string(),
+        role: role_for(email),
+        local: false,
+        birch_email: email.to_string(),
+        birch_password: password.to_string(),
+        verified_keys: None,
+    }))
+}
+
+/// Logs an operator in, asking Birch to confirm a Birch login.
+/// This is the only place login outcomes are counted towards the account lockout,
+/// the Birch calls server functions make afterwards are not logins.
+/// Birch confirms a login by answering with the keys, `Operator::keys` hands them out instead of asking again.
+#[cfg(feature = "ssr")]
+pub async fn authenticate_verified(
+    email: &str,
+    password: &str,
+) -> Result<Option<Operator>, leptos::prelude::ServerFnError> {
+    match authenticate(email, password)? {
+        Some(operator) if operator.local => Ok(Some(operator)),
+        Some(operator) => match crate::birch::fetch_keys(&operator.birch_email, &operator.birch_password).await? {
+            Some(key_response) => {
+                crate::rate_limit::record_successful_login(&operator.email);
+                crate::metrics::record_login("success");
+                Ok(Some(Operator {
+                    verified_keys: Some(key_response),
+                    ..operator
+                }))
+            }
+            None => {
+                println!("Failed Birch login for {}", operator.email);
+                crate::rate_limit::record_failed_login(&operator.email);
+                crate::metrics::record_login("failure");
+                Ok(None)
+            }
+        },
+        None => Ok(None),
+    }
+}
+
+#[cfg(feature = "ssr")]
+pub fn require_role(operator: &Operator, minimum: Role) -> Result<(), leptos::prelude::ServerFnError> {
+    match operator.role >= minimum {
+        true => Ok(()),
+        false => {
+            println!(
+                "Denied {} ({}) an action requiring {}",
+                operator.email,
+                operator.role.name(),
+                minimum.name()
+            );
+            Err(leptos::prelude::ServerFnError::new(format!(
+                "Forbidden, this action requires the {} role",
+                minimum.name()
+            )))
+        }
+    }
+}
@@ -0,0 +1,25 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    use actix_web::{App, HttpServer, web};
+    use tulip::mock_birch::MockBirch;
+
+    let addr = std::env::var("MOCK_BIRCH_ADDR").unwrap_or_else(|_| String::from("127.0.0.1:3100"));
+
+    let mock = match std::env::var("MOCK_BIRCH_STORE") {
+        Ok(path) => MockBirch::from_file(path.into()).unwrap_or_else(|err| panic!("{}", err)),
+        Err(_) => MockBirch::seeded(),
+    };
+
+    let mock = web::Data::new(mock);
+
+    println!("Mock Birch listening on http://{}", addr);
+
+    HttpServer::new(move || App::new().app_data(mock.clone()).configure(tulip::mock_birch::routes))
+        .bind(&addr)?
+        .run()
+        .await
+}
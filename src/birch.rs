@@ -0,0 +1,181 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Calls from the Tulip server to Birch, Catenary's API server which stores the realtime keys.
+
+use crate::app::{EachPasswordRow, KeyResponse};
+use crate::effective_config::AlpenroseFeedConfig;
+use crate::ingestion::IngestionStatusResponse;
+use leptos::prelude::ServerFnError;
+
+/// Whether a feed id stays within one URL path segment: only `[A-Za-z0-9._~-]`, and not "." or "..", which URLs resolve.
+pub fn is_valid_feed_id(feed_id: &str) -> bool {
+    let unreserved = |x: char| x.is_ascii_alphanumeric() || matches!(x, '.' | '_' | '~' | '-');
+
+    !feed_id.is_empty() && feed_id.chars().all(unreserved) && !feed_id.chars().all(|x| x == '.')
+}
+
+/// Birch's URL for one feed, e.g. /setrealtimekey/{feed_id}/.
+/// Feed ids come from the browser and are sent with Tulip's credentials, so any that could leave their segment are refused.
+fn feed_url(endpoint: &str, feed_id: &str) -> Result<String, ServerFnError> {
+    if !is_valid_feed_id(feed_id) {
+        return Err(ServerFnError::new(format!("{:?} is not a valid feed id", feed_id)));
+    }
+
+    Ok(format!("{}/{}/{}/", crate::config::config().birch_url, endpoint, feed_id))
+}
+
+/// Fetches every feed's keys. `Ok(None)` means Birch rejected the credentials.
+pub async fn fetch_keys(
+    birch_email: &str,
+    birch_password: &str,
+) -> Result<Option<KeyResponse>, ServerFnError> {
+    let client = reqwest::Client::new();
+
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert("Content-Type", "application/x-www-form-urlencoded".parse()?);
+
+    let mut params = std::collections::HashMap::new();
+    params.insert("email", birch_email);
+    params.insert("password", birch_password);
+
+    println!("Sending to Birch as {}", birch_email);
+
+    let started = std::time::Instant::now();
+
+    let response = client
+        .post(format!("{}/getrealtimekeys", crate::config::config().birch_url))
+        .headers(headers)
+        .form(&params)
+        .send()
+        .await;
+
+    crate::metrics::observe_birch_call("getrealtimekeys", response.as_ref().ok().map(|x| x.status().as_u16()), started);
+
+    let response = response?;
+
+    match response.status() {
+        reqwest::StatusCode::OK => {
+            let response_text = response.text().await?;
+            println!("Recieved response back from birch");
+
+            let key_response: KeyResponse = serde_json::from_str(&response_text)?;
+
+            Ok(Some(key_response))
+        }
+        reqwest::StatusCode::UNAUTHORIZED => {
+            println!("Unauthorized");
+            Ok(None)
+        }
+        _ => {
+            println!("Error, {}", response.status());
+            Err(ServerFnError::new(format!("Error, {}, \n{}", response.status(), response.text().await.unwrap())))
+        }
+    }
+}
+
+/// Posts one feed's keys to /setrealtimekey/{feed_id}/. `Ok(false)` means Birch rejected the credentials.
+pub async fn set_key(
+    birch_email: &str,
+    birch_password: &str,
+    feed_id: &str,
+    data_to_send: &EachPasswordRow,
+) -> Result<bool, ServerFnError> {
+    let url = feed_url("setrealtimekey", feed_id)?;
+    let client = reqwest::Client::new();
+
+    let started = std::time::Instant::now();
+
+    let response = client
+        .post(url)
+        .header("email", birch_email)
+        .header("password", birch_password)
+        .body(ron::ser::to_string(data_to_send)?)
+        .send()
+        .await;
+
+    crate::metrics::observe_birch_call("setrealtimekey", response.as_ref().ok().map(|x| x.status().as_u16()), started);
+
+    let response = response?;
+
+    let status = response.status();
+
+    match status {
+        reqwest::StatusCode::OK => Ok(true),
+        reqwest::StatusCode::UNAUTHORIZED => Ok(false),
+        _ => {
+            let text = response.text().await;
+
+            match text {
+                Ok(text) => {
+                    println!(
+                        "recieved strange answer from birch on setrealtimekey, {} text {}",
+                        status, text
+                    );
+                }
+                Err(err) => {
+                    println!("error on birch setrealtimekey {} err {}", status, err);
+                }
+            }
+            Err(ServerFnError::new("Data did not submit correctly"))
+        }
+    }
+}
+
+/// Alpenrose's resolved config for one feed, from /getrealtimeconfig/{feed_id}/. `Ok(None)` means Birch rejected the credentials.
+pub async fn fetch_effective_config(
+    birch_email: &str,
+    birch_password: &str,
+    feed_id: &str,
+) -> Result<Option<AlpenroseFeedConfig>, ServerFnError> {
+    let url = feed_url("getrealtimeconfig", feed_id)?;
+    let client = reqwest::Client::new();
+
+    let started = std::time::Instant::now();
+
+    let response = client
+        .get(url)
+        .header("email", birch_email)
+        .header("password", birch_password)
+        .send()
+        .await;
+
+    crate::metrics::observe_birch_call("getrealtimeconfig", response.as_ref().ok().map(|x| x.status().as_u16()), started);
+
+    let response = response?;
+
+    match response.status() {
+        reqwest::StatusCode::OK => Ok(Some(serde_json::from_str(&response.text().await?)?)),
+        reqwest::StatusCode::UNAUTHORIZED => Ok(None),
+        reqwest::StatusCode::NOT_FOUND => Err(ServerFnError::new(format!("Alpenrose has no config for feed {}", feed_id))),
+        status => Err(ServerFnError::new(format!("Error, {}, \n{}", status, response.text().await.unwrap_or_default()))),
+    }
+}
+
+/// Alpenrose's recent fetches of every feed, from /getrealtimestatus. `Ok(None)` means Birch rejected the credentials.
+pub async fn fetch_ingestion_status(
+    birch_email: &str,
+    birch_password: &str,
+) -> Result<Option<IngestionStatusResponse>, ServerFnError> {
+    let client = reqwest::Client::new();
+
+    let started = std::time::Instant::now();
+
+    let response = client
+        .get(format!("{}/getrealtimestatus", crate::config::config().birch_url))
+        .header("email", birch_email)
+        .header("password", birch_password)
+        .send()
+        .await;
+
+    crate::metrics::observe_birch_call("getrealtimestatus", response.as_ref().ok().map(|x| x.status().as_u16()), started);
+
+    let response = response?;
+
+    match response.status() {
+        reqwest::StatusCode::OK => Ok(Some(serde_json::from_str(&response.text().await?)?)),
+        reqwest::StatusCode::UNAUTHORIZED => Ok(None),
+        status => Err(ServerFnError::new(format!("Error, {}, \n{}", status, response.text().await.unwrap_or_default()))),
+    }
+}
@@ -0,0 +1,104 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Server side configuration for a Tulip deployment.
+// Loaded once from the RON file pointed to by the TULIP_CONFIG environment variable,
+// falling back to ./tulip.ron, and to the defaults below if neither exists.
+
+use crate::auth::Role;
+use crate::rate_limit::RateLimitConfig;
+use crate::robots::RobotsConfig;
+use crate::security::SecurityHeadersConfig;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct TulipConfig {
+    /// Where this Tulip is reachable, e.g. "https://tulip.catenarymaps.org", for absolute links in the sitemap.
+    /// Unset uses the host of each request.
+    pub public_url: Option<String>,
+    /// Birch's base URL, without a trailing slash.
+    pub birch_url: String,
+    /// The fetch interval Alpenrose uses for feeds without one, shown next to feeds left on the default.
+    pub alpenrose_default_fetch_interval_ms: u32,
+    /// Role granted to each operator email.
+    pub roles: BTreeMap<String, Role>,
+    /// Role given to an account Birch accepts but which is not listed in `roles`.
+    pub default_role: Role,
+    /// RON file holding Tulip's own operator accounts. Unset disables local accounts.
+    pub users_file: Option<String>,
+    /// Service credentials Tulip uses with Birch on behalf of local accounts, and for the public feed pages.
+    pub birch_email: Option<String>,
+    pub birch_password: Option<String>,
+    /// Append-only JSON lines file every key read and write is recorded in.
+    pub audit_log_file: String,
+    /// JSON lines file keeping every version of each feed's keys, including secret values.
+    pub history_file: String,
+    /// IANA zone of each feed's agency, e.g. "America/Los_Angeles", for showing times in agency time.
+    pub agency_time_zones: BTreeMap<String, String>,
+    /// Feeds whose key changes need a second Key Editor's approval. "*" means every feed.
+    pub approval_required_feeds: Vec<String>,
+    /// RON file holding change requests awaiting or past approval.
+    pub change_requests_file: String,
+    pub rate_limit: RateLimitConfig,
+    pub security_headers: SecurityHeadersConfig,
+    pub robots: RobotsConfig,
+    /// Bearer token Prometheus must send to read /metrics. Unset leaves /metrics open.
+    pub metrics_token: Option<String>,
+    /// The file this config was read from, `None` when running on the defaults.
+    #[serde(skip)]
+    pub source: Option<String>,
+}
+
+impl Default for TulipConfig {
+    fn default() -> Self {
+        TulipConfig {
+            public_url: None,
+            birch_url: String::from("https://birch.catenarymaps.org"),
+            alpenrose_default_fetch_interval_ms: 10_000,
+            roles: BTreeMap::new(),
+            default_role: Role::Viewer,
+            users_file: None,
+            birch_email: None,
+            birch_password: None,
+            audit_log_file: String::from("tulip_audit.jsonl"),
+            history_file: String::from("tulip_history.jsonl"),
+            agency_time_zones: BTreeMap::new(),
+            approval_required_feeds: vec![],
+            change_requests_file: String::from("tulip_change_requests.ron"),
+            rate_limit: RateLimitConfig::default(),
+            security_headers: SecurityHeadersConfig::default(),
+            robots: RobotsConfig::default(),
+            metrics_token: None,
+            source: None,
+        }
+    }
+}
+
+static CONFIG: OnceLock<TulipConfig> = OnceLock::new();
+
+pub fn config() -> &'static TulipConfig {
+    CONFIG.get_or_init(|| {
+        let path = std::env::var("TULIP_CONFIG").unwrap_or_else(|_| String::from("tulip.ron"));
+
+        match std::fs::read_to_string(&path) {
+            Ok(text) => match ron::from_str::<TulipConfig>(&text) {
+                Ok(config) => {
+                    println!("Loaded Tulip config from {}", path);
+                    TulipConfig {
+                        source: Some(path),
+                        ..config
+                    }
+                }
+                Err(err) => panic!("Could not parse Tulip config {}: {}", path, err),
+            },
+            Err(_) => {
+                println!("No Tulip config at {}, using defaults", path);
+                TulipConfig::default()
+            }
+        }
+    })
+}
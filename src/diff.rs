@@ -0,0 +1,68 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+use leptos::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiffLine {
+    Same(String),
+    Removed(String),
+    Added(String),
+}
+
+/// Line by line diff using the longest common subsequence.
+/// Key entries are a few dozen lines of RON, so the quadratic table is fine.
+pub fn diff_lines(before: &str, after: &str) -> Vec<DiffLine> {
+    let before = before.lines().collect::<Vec<_>>();
+    let after = after.lines().collect::<Vec<_>>();
+
+    // common[i][j] is the LCS length of before[i..] and after[j..]
+    let mut common = vec![vec![0usize; after.len() + 1]; before.len() + 1];
+
+    for i in (0..before.len()).rev() {
+        for j in (0..after.len()).rev() {
+            common[i][j] = match before[i] == after[j] {
+                true => common[i + 1][j + 1] + 1,
+                false => common[i + 1][j].max(common[i][j + 1]),
+            };
+        }
+    }
+
+    let mut lines = vec![];
+    let (mut i, mut j) = (0, 0);
+
+    while i < before.len() && j < after.len() {
+        if before[i] == after[j] {
+            lines.push(DiffLine::Same(before[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if common[i + 1][j] >= common[i][j + 1] {
+            lines.push(DiffLine::Removed(before[i].to_string()));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(after[j].to_string()));
+            j += 1;
+        }
+    }
+
+    lines.extend(before[i..].iter().map(|x| DiffLine::Removed(x.to_string())));
+    lines.extend(after[j..].iter().map(|x| DiffLine::Added(x.to_string())));
+
+    lines
+}
+
+#[component]
+pub fn LineDiff(before: String, after: String) -> impl IntoView {
+    view! {
+        <pre class="my-4 p-4 rounded-md bg-gray dark:bg-darksky text-wrap overflow-x-scroll"><code>
+            {
+                diff_lines(&before, &after).into_iter().map(|line| match line {
+                    DiffLine::Same(text) => view! { <div>{format!("  {}", text)}</div> }.into_any(),
+                    DiffLine::Removed(text) => view! { <div class="text-red-600 dark:text-red-400">{format!("- {}", text)}</div> }.into_any(),
+                    DiffLine::Added(text) => view! { <div class="text-green-600 dark:text-green-400">{format!("+ {}", text)}</div> }.into_any(),
+                }).collect_view()
+            }
+        </code></pre>
+    }
+}
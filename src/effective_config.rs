@@ -0,0 +1,235 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// What Alpenrose actually uses for a feed: the URLs after falling back to the feed's defaults,
+// the interval after falling back to Alpenrose's default, and where the keys are injected.
+// Birch resolves it at /getrealtimeconfig/{feed_id}/, Tulip marks which values come from the feed's overrides.
+
+use crate::app::{EachPasswordRow, KeyFormat};
+use crate::i18n::*;
+use crate::interval::{FetchInterval, format_ms};
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use leptos_i18n::I18nContext;
+use serde::{Deserialize, Serialize};
+
+/// Birch's answer from /getrealtimeconfig/{feed_id}/. Never contains key values.
+#[derive(Serialize, Clone, Deserialize, Debug, PartialEq)]
+pub struct AlpenroseFeedConfig {
+    pub schedule_url: Option<String>,
+    pub realtime_vehicle_positions: Option<String>,
+    pub realtime_trip_updates: Option<String>,
+    pub realtime_alerts: Option<String>,
+    pub fetch_interval_ms: u32,
+    pub key_formats: Vec<KeyFormat>,
+    /// How many entries of `PasswordFormat::passwords` Alpenrose has loaded.
+    pub password_count: usize,
+}
+
+#[derive(Serialize, Clone, Copy, Deserialize, Debug, PartialEq, Eq)]
+pub enum FeedUrlKind {
+    Schedule,
+    Vehicles,
+    TripUpdates,
+    Alerts,
+}
+
+#[derive(Serialize, Clone, Deserialize, Debug, PartialEq)]
+pub struct EffectiveUrl {
+    pub kind: FeedUrlKind,
+    /// `None` when Alpenrose does not fetch this feed type.
+    pub url: Option<String>,
+    pub overridden: bool,
+    /// The feed has an override Alpenrose is not using yet, it reloads keys periodically.
+    pub pending_override: Option<String>,
+}
+
+#[derive(Serialize, Clone, Deserialize, Debug, PartialEq)]
+pub struct EffectiveConfig {
+    pub urls: Vec<EffectiveUrl>,
+    /// As stored in Birch.
+    pub interval: FetchInterval,
+    /// As used by Alpenrose.
+    pub fetch_interval_ms: u32,
+    pub key_formats: Vec<KeyFormat>,
+    pub password_count: usize,
+}
+
+/// Combines Alpenrose's resolved config with the feed's own row, to show where each value comes from.
+pub fn resolve(alpenrose: AlpenroseFeedConfig, row: Option<&EachPasswordRow>) -> EffectiveConfig {
+    let format = row.and_then(|row| row.passwords.as_ref());
+
+    let url = |kind: FeedUrlKind, url: Option<String>, override_url: Option<&String>| EffectiveUrl {
+        kind,
+        overridden: override_url.is_some() && override_url == url.as_ref(),
+        pending_override: override_url.filter(|x| Some(*x) != url.as_ref()).cloned(),
+        url,
+    };
+
+    EffectiveConfig {
+        urls: vec![
+            url(FeedUrlKind::Schedule, alpenrose.schedule_url, format.and_then(|x| x.override_schedule_url.as_ref())),
+            url(
+                FeedUrlKind::Vehicles,
+                alpenrose.realtime_vehicle_positions,
+                format.and_then(|x| x.override_realtime_vehicle_positions.as_ref()),
+            ),
+            url(
+                FeedUrlKind::TripUpdates,
+                alpenrose.realtime_trip_updates,
+                format.and_then(|x| x.override_realtime_trip_updates.as_ref()),
+            ),
+            url(FeedUrlKind::Alerts, alpenrose.realtime_alerts, format.and_then(|x| x.override_alerts.as_ref())),
+        ],
+        interval: FetchInterval::from_birch(row.and_then(|row| row.fetch_interval_ms)),
+        fetch_interval_ms: alpenrose.fetch_interval_ms,
+        key_formats: alpenrose.key_formats,
+        password_count: alpenrose.password_count,
+    }
+}
+
+fn url_label(i18n: I18nContext<Locale>, kind: FeedUrlKind) -> &'static str {
+    match kind {
+        FeedUrlKind::Schedule => t_string!(i18n, config_url_schedule),
+        FeedUrlKind::Vehicles => t_string!(i18n, config_url_vehicles),
+        FeedUrlKind::TripUpdates => t_string!(i18n, config_url_trip_updates),
+        FeedUrlKind::Alerts => t_string!(i18n, config_url_alerts),
+    }
+}
+
+/// e.g. "Header api_key: key 1" or "URL query ?api_key=key 1".
+pub fn describe_key_format(i18n: I18nContext<Locale>, key_format: &KeyFormat, index: usize) -> String {
+    match key_format {
+        KeyFormat::Header(name) => t_string!(i18n, config_key_header, name = name, index = index + 1).to_string(),
+        KeyFormat::UrlQuery(name) => t_string!(i18n, config_key_query, name = name, index = index + 1).to_string(),
+    }
+}
+
+#[cfg(feature = "ssr")]
+async fn load_effective_config_checked(
+    master_email: &str,
+    master_password: &str,
+    feed_id: &str,
+) -> Result<Option<EffectiveConfig>, ServerFnError> {
+    let mut operator = match crate::auth::authenticate_verified(master_email, master_password).await? {
+        Some(operator) => operator,
+        None => return Ok(None),
+    };
+
+    let mut key_response = match operator.keys().await? {
+        Some(key_response) => key_response,
+        None => return Ok(None),
+    };
+
+    let alpenrose =
+        match crate::birch::fetch_effective_config(&operator.birch_email, &operator.birch_password, feed_id).await? {
+            Some(alpenrose) => alpenrose,
+            None => return Ok(None),
+        };
+
+    Ok(Some(resolve(alpenrose, key_response.passwords.remove(feed_id).as_ref())))
+}
+
+/// Open to every role, nothing returned is secret.
+#[server(endpoint = "load_effective_config")]
+pub async fn load_effective_config(
+    master_email: String,
+    master_password: String,
+    feed_id: String,
+) -> Result<Option<EffectiveConfig>, ServerFnError> {
+    load_effective_config_checked(&master_email, &master_password, &feed_id).await
+}
+
+/// Per feed in the key manager, loads Alpenrose's config when opened.
+#[component]
+pub fn EffectiveConfigPanel(feed_id: String, master_email: Signal<String>, master_password: Signal<String>) -> impl IntoView {
+    let i18n = use_i18n();
+
+    let (config, set_config) = signal(None::<Result<EffectiveConfig, String>>);
+    let (loading, set_loading) = signal(false);
+
+    let load = move |feed_id: String| {
+        let (master_email, master_password) = (master_email.get_untracked(), master_password.get_untracked());
+        set_loading.set(true);
+
+        spawn_local(async move {
+            set_config.set(match load_effective_config(master_email, master_password, feed_id).await {
+                Ok(Some(config)) => Some(Ok(config)),
+                Ok(None) => Some(Err(t_string!(i18n, not_authorised).to_string())),
+                Err(err) => Some(Err(err.to_string())),
+            });
+            set_loading.set(false);
+        });
+    };
+
+    view! {
+        <button
+            class="underline text-blue-500 dark:text-blue-300 mr-2"
+            disabled=move || loading.get()
+            on:click={
+                let feed_id = feed_id.clone();
+                move |_| load(feed_id.clone())
+            }
+        >
+            {move || if config.get().is_some() { t_string!(i18n, config_reload) } else { t_string!(i18n, config_show) }}
+        </button>
+        {
+            move || config.get().map(|config| match config {
+                Ok(config) => view! {
+                    <div class="my-2">
+                        <p class="font-semibold">{t!(i18n, config_fetches)}</p>
+                        <ul>
+                            {
+                                config.urls.iter().map(|effective_url| view! {
+                                    <li>
+                                        <span class="mr-2">{format!("{}:", url_label(i18n, effective_url.kind))}</span>
+                                        <span class="font-mono break-all mr-2">{effective_url.url.clone().unwrap_or_else(|| t_string!(i18n, config_not_fetched).to_string())}</span>
+                                        {
+                                            effective_url.url.is_some().then(|| match effective_url.overridden {
+                                                true => t_string!(i18n, config_override),
+                                                false => t_string!(i18n, config_default),
+                                            })
+                                        }
+                                        {
+                                            effective_url.pending_override.clone().map(|pending| view! {
+                                                <p class="text-sm">{t_string!(i18n, config_pending_override, url = pending).to_string()}</p>
+                                            })
+                                        }
+                                    </li>
+                                }).collect_view()
+                            }
+                        </ul>
+                        <p>
+                            {
+                                let interval = format_ms(config.fetch_interval_ms);
+                                match config.interval {
+                                    FetchInterval::Default => t_string!(i18n, config_every_default, interval = interval).to_string(),
+                                    FetchInterval::Every { ms } if ms != config.fetch_interval_ms => {
+                                        t_string!(i18n, config_every_pending, interval = interval, pending = format_ms(ms)).to_string()
+                                    }
+                                    FetchInterval::Every { .. } => t_string!(i18n, config_every_set, interval = interval).to_string(),
+                                }
+                            }
+                        </p>
+                        <p class="font-semibold">{t!(i18n, config_injected)}</p>
+                        {
+                            match config.key_formats.is_empty() {
+                                true => view! { <p>{t!(i18n, config_no_keys)}</p> }.into_any(),
+                                false => view! {
+                                    <ul>
+                                        {config.key_formats.iter().enumerate().map(|(index, key_format)| view! {
+                                            <li class="font-mono">{describe_key_format(i18n, key_format, index)}</li>
+                                        }).collect_view()}
+                                    </ul>
+                                }.into_any(),
+                            }
+                        }
+                        <p>{t_string!(i18n, config_password_entries, count = config.password_count).to_string()}</p>
+                    </div>
+                }.into_any(),
+                Err(err) => view! { <p>{format!("❌ {}", err)}</p> }.into_any(),
+            })
+        }
+    }
+}
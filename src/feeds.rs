@@ -0,0 +1,154 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// A public page for each feed Alpenrose fetches: when it last fetched the feed successfully,
+// and links to Birch's raw GTFS-RT output, which is public already. Tulip reads Birch's status
+// with its service credentials, nothing on these pages needs a login or reveals a key.
+
+use crate::app::{GTFSRAWOPTIONS, Nav};
+use crate::i18n::*;
+use crate::page_meta::PageMeta;
+use crate::time::Time;
+use leptos::prelude::*;
+use leptos_router::hooks::use_params_map;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "ssr")]
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Clone, Deserialize, Debug, PartialEq)]
+pub struct PublicFeed {
+    pub feed_id: String,
+    /// When Alpenrose last fetched the feed successfully, in milliseconds since the unix epoch.
+    pub last_success_ms: Option<i64>,
+    /// Where the raw data is linked from.
+    pub birch_url: String,
+}
+
+/// Every feed in Birch's ingestion status whose id can appear in a URL, by feed id.
+#[cfg(feature = "ssr")]
+pub async fn public_feeds() -> Result<BTreeMap<String, PublicFeed>, ServerFnError> {
+    let config = crate::config::config();
+
+    let (Some(birch_email), Some(birch_password)) = (&config.birch_email, &config.birch_password) else {
+        return Err(ServerFnError::new("Feed pages need birch_email and birch_password in tulip.ron"));
+    };
+
+    let status = match crate::birch::fetch_ingestion_status(birch_email, birch_password).await? {
+        Some(status) => status,
+        None => return Err(ServerFnError::new("Birch rejected Tulip's service credentials")),
+    };
+
+    Ok(status
+        .feeds
+        .into_iter()
+        .filter(|(feed_id, _)| crate::birch::is_valid_feed_id(feed_id))
+        .map(|(feed_id, status)| {
+            let feed = PublicFeed {
+                feed_id: feed_id.clone(),
+                last_success_ms: status.last_success_ms,
+                birch_url: config.birch_url.clone(),
+            };
+            (feed_id, feed)
+        })
+        .collect())
+}
+
+/// `Ok(None)` when Birch has no such feed.
+#[server(endpoint = "load_public_feed")]
+pub async fn load_public_feed(feed_id: String) -> Result<Option<PublicFeed>, ServerFnError> {
+    Ok(public_feeds().await?.remove(feed_id.trim()))
+}
+
+#[cfg(feature = "ssr")]
+fn set_status(status: actix_web::http::StatusCode) {
+    if let Some(response) = use_context::<leptos_actix::ResponseOptions>() {
+        response.set_status(status);
+    }
+}
+
+#[component]
+pub fn FeedPage() -> impl IntoView {
+    let i18n = use_i18n();
+    let params = use_params_map();
+
+    let feed = Resource::new(
+        move || params.with(|x| x.get("feed_id")).unwrap_or_default(),
+        load_public_feed,
+    );
+
+    view! {
+        <Nav/>
+        <main class="m-8">
+            <Suspense fallback=move || view! { <p>{t!(i18n, loading)}</p> }>
+                {move || Suspend::new(async move {
+                    match feed.await {
+                        Ok(Some(feed)) => view! { <FeedDetails feed=feed/> }.into_any(),
+                        Ok(None) => {
+                            #[cfg(feature = "ssr")]
+                            set_status(actix_web::http::StatusCode::NOT_FOUND);
+
+                            view! {
+                                <PageMeta title=move || t_string!(i18n, title_not_found) noindex=true/>
+                                <p>{t!(i18n, feed_not_found)}</p>
+                            }
+                            .into_any()
+                        }
+                        Err(err) => {
+                            #[cfg(feature = "ssr")]
+                            set_status(actix_web::http::StatusCode::SERVICE_UNAVAILABLE);
+
+                            view! {
+                                <PageMeta title=move || t_string!(i18n, title_not_found) noindex=true/>
+                                <p>{format!("❌ {}", err)}</p>
+                            }
+                            .into_any()
+                        }
+                    }
+                })}
+            </Suspense>
+        </main>
+    }
+}
+
+#[component]
+fn FeedDetails(feed: PublicFeed) -> impl IntoView {
+    let i18n = use_i18n();
+
+    let (title_feed_id, description_feed_id) = (feed.feed_id.clone(), feed.feed_id.clone());
+
+    let raw_url = |feed_type: &str, format: Option<&str>| {
+        let format = format.map(|x| format!("&format={}", x)).unwrap_or_default();
+        format!("{}/gtfs_rt?feed_id={}&feed_type={}{}", feed.birch_url, feed.feed_id, feed_type, format)
+    };
+
+    view! {
+        <PageMeta
+            title=move || t_string!(i18n, title_feed, feed_id = title_feed_id.clone()).to_string()
+            description=move || t_string!(i18n, description_feed, feed_id = description_feed_id.clone()).to_string()
+        />
+        <h1 class="text-2xl font-bold text-tulip mb-4">{feed.feed_id.clone()}</h1>
+        <p>
+            {t!(i18n, feed_last_success)}" "
+            {
+                match feed.last_success_ms {
+                    Some(time_ms) => view! { <Time time_ms=time_ms feed_id=feed.feed_id.clone()/> }.into_any(),
+                    None => view! { <span>{t!(i18n, stale_never)}</span> }.into_any(),
+                }
+            }
+        </p>
+        <h2 class="text-xl font-bold text-tulip mt-4 mb-2">{t!(i18n, feed_raw_data)}</h2>
+        <ul>
+            {
+                GTFSRAWOPTIONS.iter().map(|(name_of_feed_type, feed_type)| view! {
+                    <li>
+                        <span class="font-semibold mr-2">{name_of_feed_type.to_string()}</span>
+                        <a class="underline text-blue-500 dark:text-blue-300 mr-2" href=raw_url(feed_type, None)>"Protobuf"</a>
+                        <a class="underline text-blue-500 dark:text-blue-300 mr-2" href=raw_url(feed_type, Some("json"))>"Json"</a>
+                        <a class="underline text-blue-500 dark:text-blue-300" href=raw_url(feed_type, Some("ron"))>"Ron"</a>
+                    </li>
+                }).collect_view()
+            }
+        </ul>
+    }
+}
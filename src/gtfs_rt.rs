@@ -0,0 +1,116 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// The timestamps of a GTFS-rt FeedMessage, read by hand to avoid a protobuf dependency.
+// Only the feed header and vehicle positions are read, everything else is skipped.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Clone, Deserialize, Debug, Default, PartialEq, Eq)]
+pub struct FeedTimestamps {
+    /// FeedHeader.timestamp, when the agency produced the feed, in milliseconds since the unix epoch.
+    pub header_ms: Option<i64>,
+    /// Vehicle positions carrying a timestamp.
+    pub vehicles: usize,
+    pub oldest_vehicle_ms: Option<i64>,
+    pub newest_vehicle_ms: Option<i64>,
+}
+
+enum Value<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+    Fixed,
+}
+
+/// Reads a varint from the front of `bytes`, advancing past it.
+fn read_varint(bytes: &mut &[u8]) -> Option<u64> {
+    let mut value = 0u64;
+
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = bytes.split_first()?;
+        *bytes = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+
+    None
+}
+
+/// A message's fields as field number and value, `None` if the bytes are not protobuf.
+fn fields(mut bytes: &[u8]) -> Option<Vec<(u64, Value<'_>)>> {
+    let mut fields = vec![];
+
+    while !bytes.is_empty() {
+        let tag = read_varint(&mut bytes)?;
+
+        let value = match tag & 7 {
+            0 => Value::Varint(read_varint(&mut bytes)?),
+            1 => {
+                bytes = bytes.get(8..)?;
+                Value::Fixed
+            }
+            2 => {
+                let length = usize::try_from(read_varint(&mut bytes)?).ok()?;
+                let (value, rest) = bytes.split_at_checked(length)?;
+                bytes = rest;
+                Value::Bytes(value)
+            }
+            5 => {
+                bytes = bytes.get(4..)?;
+                Value::Fixed
+            }
+            // deprecated groups, which GTFS-rt does not use
+            _ => return None,
+        };
+
+        fields.push((tag >> 3, value));
+    }
+
+    Some(fields)
+}
+
+/// A uint64 POSIX time field, in milliseconds. GTFS-rt leaves unset timestamps at 0.
+fn timestamp_ms(message: &[u8], number: u64) -> Option<i64> {
+    fields(message)?.into_iter().find_map(|(field, value)| match (field, value) {
+        (field, Value::Varint(seconds)) if field == number && seconds > 0 => {
+            i64::try_from(seconds).ok()?.checked_mul(1000)
+        }
+        _ => None,
+    })
+}
+
+/// `None` unless the bytes are a FeedMessage, which always has a header.
+pub fn feed_timestamps(bytes: &[u8]) -> Option<FeedTimestamps> {
+    let mut timestamps = FeedTimestamps::default();
+    let mut has_header = false;
+
+    // FeedMessage: header = 1, entity = 2
+    for (field, value) in fields(bytes)? {
+        match (field, value) {
+            (1, Value::Bytes(header)) => {
+                has_header = true;
+                // FeedHeader: timestamp = 3
+                timestamps.header_ms = timestamp_ms(header, 3);
+            }
+            (2, Value::Bytes(entity)) => {
+                // FeedEntity: vehicle = 4, VehiclePosition: timestamp = 5
+                for (field, value) in fields(entity)? {
+                    if let (4, Value::Bytes(vehicle)) = (field, value)
+                        && let Some(time_ms) = timestamp_ms(vehicle, 5)
+                    {
+                        timestamps.vehicles += 1;
+                        timestamps.oldest_vehicle_ms = Some(timestamps.oldest_vehicle_ms.map_or(time_ms, |x| x.min(time_ms)));
+                        timestamps.newest_vehicle_ms = Some(timestamps.newest_vehicle_ms.map_or(time_ms, |x| x.max(time_ms)));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    has_header.then_some(timestamps)
+}
@@ -0,0 +1,85 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Probe and build information endpoints for deployments.
+// /healthz answers while the process is up, /readyz only once Tulip can reach Birch,
+// and /version says which build is running.
+
+use actix_web::HttpResponse;
+use serde::Serialize;
+use std::time::Duration;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Readiness {
+    pub ready: bool,
+    /// The config file in use, or "defaults".
+    pub config: String,
+    pub birch_url: String,
+    /// Why Birch could not be reached, `None` when it answered.
+    pub birch_error: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct VersionInfo {
+    pub version: &'static str,
+    pub git_hash: &'static str,
+    /// RFC 3339, UTC.
+    pub build_time: String,
+}
+
+/// When this binary was built, set by build.rs.
+pub fn build_time() -> Option<chrono::DateTime<chrono::Utc>> {
+    env!("TULIP_BUILD_TIME")
+        .parse::<i64>()
+        .ok()
+        .and_then(|x| chrono::DateTime::from_timestamp(x, 0))
+}
+
+pub fn version_info() -> VersionInfo {
+    VersionInfo {
+        version: env!("CARGO_PKG_VERSION"),
+        git_hash: env!("TULIP_GIT_HASH"),
+        build_time: build_time().map(|x| x.to_rfc3339()).unwrap_or_default(),
+    }
+}
+
+/// Any HTTP answer counts as reachable, Birch has no unauthenticated health route.
+pub async fn check_birch(birch_url: &str) -> Result<(), String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .map_err(|err| err.to_string())?;
+
+    match client.get(birch_url).send().await {
+        Ok(_) => Ok(()),
+        Err(err) => Err(err.without_url().to_string()),
+    }
+}
+
+pub async fn healthz() -> HttpResponse {
+    HttpResponse::Ok().json(serde_json::json!({ "status": "ok" }))
+}
+
+pub async fn readyz() -> HttpResponse {
+    // panics while loading if the config file exists but does not parse
+    let config = crate::config::config();
+
+    let birch_error = check_birch(&config.birch_url).await.err();
+
+    let readiness = Readiness {
+        ready: birch_error.is_none(),
+        config: config.source.clone().unwrap_or_else(|| String::from("defaults")),
+        birch_url: config.birch_url.clone(),
+        birch_error,
+    };
+
+    match readiness.ready {
+        true => HttpResponse::Ok().json(readiness),
+        false => HttpResponse::ServiceUnavailable().json(readiness),
+    }
+}
+
+pub async fn version() -> HttpResponse {
+    HttpResponse::Ok().json(version_info())
+}
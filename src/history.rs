@@ -0,0 +1,336 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Every version of a feed's keys submitted through Tulip, so a bad change can be rolled back.
+// Versions are JSON lines in the file set as history_file in tulip.ron. That file holds real
+// secret values and should be protected like Birch's own database; the browser only ever sees
+// fingerprinted text.
+
+use crate::app::{EachPasswordRow, Nav, SubmitOutcome};
+use crate::diff::LineDiff;
+use crate::i18n::*;
+use crate::page_meta::PageMeta;
+use crate::time::Time;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use leptos_router::hooks::use_query_map;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Clone, Deserialize, Debug)]
+pub struct KeyVersion {
+    pub feed_id: String,
+    /// Milliseconds since the unix epoch, UTC.
+    pub time_ms: i64,
+    pub author: String,
+    pub note: String,
+    pub row: EachPasswordRow,
+}
+
+/// A version as sent to the browser, with secrets fingerprinted.
+#[derive(Serialize, Clone, Deserialize, Debug)]
+pub struct KeyVersionSummary {
+    /// Position in the feed's history, oldest first, used to restore it.
+    pub index: usize,
+    pub time_ms: i64,
+    pub author: String,
+    pub note: String,
+    pub text: String,
+}
+
+#[cfg(feature = "ssr")]
+static HISTORY_FILE_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+/// Every feed's versions, oldest first.
+#[cfg(feature = "ssr")]
+fn all_versions() -> Result<Vec<KeyVersion>, ServerFnError> {
+    let path = &crate::config::config().history_file;
+
+    let text = {
+        let _guard = HISTORY_FILE_LOCK.lock().unwrap();
+        match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err.into()),
+        }
+    };
+
+    let mut versions = vec![];
+
+    for line in text.lines().filter(|x| !x.trim().is_empty()) {
+        versions.push(serde_json::from_str(line)?);
+    }
+
+    Ok(versions)
+}
+
+/// The feed's versions, oldest first.
+#[cfg(feature = "ssr")]
+pub fn versions_for(feed_id: &str) -> Result<Vec<KeyVersion>, ServerFnError> {
+    Ok(all_versions()?.into_iter().filter(|x| x.feed_id == feed_id).collect())
+}
+
+/// When each feed's keys last changed through Tulip.
+#[cfg(feature = "ssr")]
+pub fn last_changed() -> Result<std::collections::BTreeMap<String, i64>, ServerFnError> {
+    let mut last_changed = std::collections::BTreeMap::new();
+
+    for version in all_versions()? {
+        let time_ms = last_changed.entry(version.feed_id).or_insert(version.time_ms);
+        *time_ms = (*time_ms).max(version.time_ms);
+    }
+
+    Ok(last_changed)
+}
+
+#[cfg(feature = "ssr")]
+fn append_version(version: &KeyVersion) -> Result<(), ServerFnError> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let path = &crate::config::config().history_file;
+    let line = serde_json::to_string(version)?;
+
+    let _guard = HISTORY_FILE_LOCK.lock().unwrap();
+
+    // it holds real keys, so only Tulip's own user may read it
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .mode(0o600)
+        .open(path)?;
+    writeln!(file, "{}", line)?;
+
+    Ok(())
+}
+
+/// Records a version Birch has accepted.
+/// The first time a feed changes through Tulip, what Birch had before is kept as well so it can be restored.
+#[cfg(feature = "ssr")]
+pub fn record_version(
+    feed_id: &str,
+    author: &str,
+    note: &str,
+    before: Option<&EachPasswordRow>,
+    row: &EachPasswordRow,
+) -> Result<(), ServerFnError> {
+    let time_ms = chrono::Utc::now().timestamp_millis();
+
+    if let Some(before) = before
+        && versions_for(feed_id)?.is_empty()
+    {
+        append_version(&KeyVersion {
+            feed_id: feed_id.to_string(),
+            time_ms,
+            author: String::from("Birch"),
+            note: String::from("Keys before Tulip kept history"),
+            row: before.clone(),
+        })?;
+    }
+
+    append_version(&KeyVersion {
+        feed_id: feed_id.to_string(),
+        time_ms,
+        author: author.to_string(),
+        note: note.to_string(),
+        row: row.clone(),
+    })
+}
+
+#[server(endpoint = "load_key_history")]
+pub async fn load_key_history(
+    master_email: String,
+    master_password: String,
+    feed_id: String,
+) -> Result<Option<Vec<KeyVersionSummary>>, ServerFnError> {
+    let operator = match crate::auth::authenticate_verified(&master_email, &master_password).await? {
+        Some(operator) => operator,
+        None => return Ok(None),
+    };
+
+    crate::auth::require_role(&operator, crate::auth::Role::KeyEditor)?;
+
+    Ok(Some(
+        versions_for(feed_id.trim())?
+            .into_iter()
+            .enumerate()
+            .map(|(index, version)| KeyVersionSummary {
+                index,
+                time_ms: version.time_ms,
+                author: version.author,
+                note: version.note,
+                text: version.row.audit_text(),
+            })
+            .collect(),
+    ))
+}
+
+#[cfg(feature = "ssr")]
+async fn restore_key_version_checked(
+    entry: &mut crate::audit::AuditEntry,
+    master_email: &str,
+    master_password: &str,
+    feed_id: &str,
+    index: usize,
+) -> Result<SubmitOutcome, ServerFnError> {
+    let mut operator = match crate::auth::authenticate_verified(master_email, master_password).await? {
+        Some(operator) => operator,
+        None => return Ok(SubmitOutcome::Unauthorised),
+    };
+
+    crate::auth::require_role(&operator, crate::auth::Role::KeyEditor)?;
+
+    let version = match versions_for(feed_id)?.into_iter().nth(index) {
+        Some(version) => version,
+        None => return Err(ServerFnError::new(format!("Feed {} has no version {}", feed_id, index))),
+    };
+
+    //restoring is a key change like any other, so it needs the same approval
+    if crate::approvals::requires_approval(feed_id) {
+        entry.action = crate::audit::AuditAction::RequestChange;
+        entry.after = Some(version.row.audit_text());
+        let id = crate::approvals::create_change_request(feed_id, &operator.email, &version.row)?;
+        return Ok(SubmitOutcome::PendingApproval(id));
+    }
+
+    let note = format!("Restored the version from {}", crate::audit::format_time_ms(version.time_ms));
+
+    match crate::app::apply_key_change(&mut operator, entry, feed_id, &version.row, &note).await? {
+        true => Ok(SubmitOutcome::Applied),
+        false => Ok(SubmitOutcome::Unauthorised),
+    }
+}
+
+#[server(endpoint = "restore_key_version")]
+pub async fn restore_key_version(
+    master_email: String,
+    master_password: String,
+    feed_id: String,
+    index: usize,
+) -> Result<SubmitOutcome, ServerFnError> {
+    use crate::audit::{AuditAction, AuditEntry, AuditOutcome};
+
+    let mut entry = AuditEntry::new(&master_email, AuditAction::RestoreKeys, Some(feed_id.trim().to_string()));
+    let result = restore_key_version_checked(&mut entry, &master_email, &master_password, feed_id.trim(), index).await;
+    entry.outcome = AuditOutcome::from_bool(&result.clone().map(|x| x != SubmitOutcome::Unauthorised));
+    crate::audit::record(entry)?;
+
+    result
+}
+
+#[component]
+pub fn KeyHistory() -> impl IntoView {
+    let i18n = use_i18n();
+
+    let query = use_query_map();
+
+    let (master_email, set_master_email) = signal(String::from(""));
+    let (master_password, set_master_password) = signal(String::from(""));
+    let (feed_id, set_feed_id) = signal(query.with_untracked(|x| x.get("feed_id")).unwrap_or_default());
+
+    let versions: RwSignal<Option<Vec<KeyVersionSummary>>> = RwSignal::new(None);
+    let (message, set_message) = signal(String::from(""));
+
+    let reload = move || {
+        let (master_email, master_password, feed_id) = (master_email.get(), master_password.get(), feed_id.get());
+
+        spawn_local(async move {
+            match load_key_history(master_email, master_password, feed_id).await {
+                Ok(Some(list)) => {
+                    set_message.set(t_string!(i18n, history_versions, count = list.len()).to_string());
+                    versions.set(Some(list));
+                }
+                Ok(None) => set_message.set(t_string!(i18n, not_authorised).to_string()),
+                Err(err) => set_message.set(err.to_string()),
+            }
+        });
+    };
+
+    view! {
+        <PageMeta title=move || t_string!(i18n, title_history) noindex=true/>
+        <Nav/>
+        <main class="p-8">
+            <h1 class="text-2xl font-bold text-tulip">{t!(i18n, title_history)}</h1>
+            <p>{t!(i18n, history_intro)}</p>
+
+            <form on:submit=move |event| {
+                event.prevent_default();
+                reload();
+            }>
+                <input
+                    type="email"
+                    placeholder=move || t_string!(i18n, email)
+                    prop:value=move || master_email.get()
+                    class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold mr-4"
+                    on:input=move |event| set_master_email.set(event_target_value(&event))
+                />
+                <input
+                    type="password"
+                    autocomplete="current-password"
+                    placeholder=move || t_string!(i18n, password)
+                    prop:value=move || master_password.get()
+                    class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold mr-4"
+                    on:input=move |event| set_master_password.set(event_target_value(&event))
+                />
+                <input
+                    type="text"
+                    placeholder=move || t_string!(i18n, feed_id)
+                    prop:value=move || feed_id.get()
+                    class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold mr-4"
+                    on:input=move |event| set_feed_id.set(event_target_value(&event))
+                />
+                <button type="submit" class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold">{t!(i18n, load)}</button>
+            </form>
+
+            <p class="font-semibold">{move || message.get()}</p>
+
+            {
+                move || versions.get().map(|list| {
+                    // each version is diffed against the one before it
+                    let previous_texts = std::iter::once(String::new())
+                        .chain(list.iter().map(|x| x.text.clone()))
+                        .collect::<Vec<_>>();
+
+                    view! {
+                        <ol class="my-4 space-y-4">
+                            {
+                                list.into_iter().zip(previous_texts).rev().map(|(version, previous_text)| {
+                                    let index = version.index;
+                                    view! {
+                                        <li>
+                                            <p>
+                                                <span class="mr-4"><Time time_ms=version.time_ms feed_id=feed_id.get_untracked()/></span>
+                                                <span class="font-semibold mr-4">{version.author.clone()}</span>
+                                                <span class="mr-4">{version.note.clone()}</span>
+                                                <button
+                                                class="bg-gray dark:bg-darksky rounded-md p-1 px-2 border-2 border-tulip text-sm font-bold"
+                                                on:click=move |_| {
+                                                    let (master_email, master_password, feed_id) = (master_email.get(), master_password.get(), feed_id.get());
+                                                    spawn_local(async move {
+                                                        match restore_key_version(master_email, master_password, feed_id, index).await {
+                                                            Ok(SubmitOutcome::Applied) => {
+                                                                set_message.set(t_string!(i18n, history_restored).to_string());
+                                                                reload();
+                                                            }
+                                                            Ok(SubmitOutcome::PendingApproval(id)) => {
+                                                                set_message.set(t_string!(i18n, history_restore_pending, id = id).to_string())
+                                                            }
+                                                            Ok(SubmitOutcome::Unauthorised) => set_message.set(t_string!(i18n, not_authorised).to_string()),
+                                                            Err(err) => set_message.set(err.to_string()),
+                                                        }
+                                                    });
+                                                }
+                                                >{t!(i18n, history_restore)}</button>
+                                            </p>
+                                            <LineDiff before=previous_text after=version.text.clone()/>
+                                        </li>
+                                    }
+                                }).collect_view()
+                            }
+                        </ol>
+                    }
+                })
+            }
+        </main>
+    }
+}
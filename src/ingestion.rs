@@ -0,0 +1,361 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// How Alpenrose's fetch workers are doing for each feed, from Birch's /getrealtimestatus,
+// with the likely reasons a feed is stale. The most common one after a key change is that
+// Alpenrose has not reloaded the keys from Birch yet.
+
+use crate::app::Nav;
+use crate::audit::format_time_ms;
+use crate::i18n::*;
+use crate::page_meta::PageMeta;
+use crate::time::Time;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use leptos_i18n::I18nContext;
+use leptos_router::hooks::use_query_map;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A feed without a successful fetch for this long is stale.
+pub const STALE_AFTER_MS: i64 = 5 * 60 * 1000;
+
+/// One fetch of one feed type from the agency.
+#[derive(Serialize, Clone, Deserialize, Debug, PartialEq)]
+pub struct FetchAttempt {
+    pub time_ms: i64,
+    /// "vehicle", "trip" or "alert".
+    pub feed_type: String,
+    /// `None` when no response came back, see `error`.
+    pub http_status: Option<u16>,
+    pub bytes: usize,
+    /// The response did not decode as GTFS-RT.
+    pub parse_error: Option<String>,
+    /// Connection errors and timeouts.
+    pub error: Option<String>,
+    /// Which entry of `PasswordFormat::passwords` was used, `None` for feeds without keys.
+    pub password_index: Option<usize>,
+}
+
+impl FetchAttempt {
+    pub fn succeeded(&self) -> bool {
+        self.http_status == Some(200) && self.parse_error.is_none() && self.error.is_none()
+    }
+}
+
+#[derive(Serialize, Clone, Deserialize, Debug, PartialEq, Default)]
+pub struct FeedIngestionStatus {
+    pub last_success_ms: Option<i64>,
+    pub last_failure_ms: Option<i64>,
+    /// When Alpenrose last loaded this feed's keys from Birch.
+    pub keys_loaded_ms: Option<i64>,
+    /// Newest first.
+    pub recent_attempts: Vec<FetchAttempt>,
+}
+
+/// Birch's answer from /getrealtimestatus.
+#[derive(Serialize, Clone, Deserialize, Debug, PartialEq)]
+pub struct IngestionStatusResponse {
+    pub feeds: BTreeMap<String, FeedIngestionStatus>,
+}
+
+#[derive(Serialize, Clone, Deserialize, Debug, PartialEq)]
+pub enum StaleReason {
+    /// The keys changed in Tulip after Alpenrose last loaded them.
+    KeysNotReloaded { changed_ms: i64, loaded_ms: Option<i64> },
+    /// The agency answered 401 or 403 to the key Alpenrose used.
+    KeyRejected { status: u16, password_index: Option<usize> },
+    RateLimited { password_index: Option<usize> },
+    ParseFailed { error: String },
+    AgencyError { status: Option<u16>, error: Option<String> },
+    NeverSucceeded,
+    /// No attempt at all recently, the worker may not be running.
+    NotFetching { last_attempt_ms: Option<i64> },
+}
+
+fn describe_password(i18n: I18nContext<Locale>, password_index: Option<usize>) -> String {
+    match password_index {
+        Some(index) => t_string!(i18n, stale_password, index = index).to_string(),
+        None => t_string!(i18n, stale_no_key).to_string(),
+    }
+}
+
+impl StaleReason {
+    pub fn describe(&self, i18n: I18nContext<Locale>) -> String {
+        match self {
+            StaleReason::KeysNotReloaded { changed_ms, loaded_ms } => {
+                let loaded = match loaded_ms {
+                    Some(loaded_ms) => t_string!(i18n, stale_loaded_at, time = format_time_ms(*loaded_ms)).to_string(),
+                    None => t_string!(i18n, stale_never).to_string(),
+                };
+                t_string!(i18n, stale_keys_not_reloaded, changed = format_time_ms(*changed_ms), loaded = loaded).to_string()
+            }
+            StaleReason::KeyRejected { status, password_index } => {
+                t_string!(i18n, stale_key_rejected, password = describe_password(i18n, *password_index), status = status).to_string()
+            }
+            StaleReason::RateLimited { password_index } => {
+                t_string!(i18n, stale_rate_limited, password = describe_password(i18n, *password_index)).to_string()
+            }
+            StaleReason::ParseFailed { error } => t_string!(i18n, stale_parse_failed, error = error).to_string(),
+            StaleReason::AgencyError { status, error } => match (status, error) {
+                (_, Some(error)) => t_string!(i18n, stale_unreachable, error = error).to_string(),
+                (Some(status), None) => t_string!(i18n, stale_http, status = status).to_string(),
+                (None, None) => t_string!(i18n, stale_fetch_failed).to_string(),
+            },
+            StaleReason::NeverSucceeded => t_string!(i18n, stale_never_succeeded).to_string(),
+            StaleReason::NotFetching { last_attempt_ms } => {
+                let since = match last_attempt_ms {
+                    Some(last_attempt_ms) => format_time_ms(*last_attempt_ms),
+                    None => t_string!(i18n, stale_since_start).to_string(),
+                };
+                t_string!(i18n, stale_not_fetching, since = since).to_string()
+            }
+        }
+    }
+}
+
+/// Whether the feed's data is out of date.
+pub fn is_stale(status: &FeedIngestionStatus, now_ms: i64) -> bool {
+    match status.last_success_ms {
+        Some(last_success_ms) => {
+            now_ms - last_success_ms > STALE_AFTER_MS
+                || status.recent_attempts.first().is_some_and(|attempt| !attempt.succeeded())
+        }
+        None => true,
+    }
+}
+
+/// The likely reasons a feed is stale, most likely first. `key_changed_ms` is the feed's last change through Tulip.
+pub fn explain(status: &FeedIngestionStatus, key_changed_ms: Option<i64>, now_ms: i64) -> Vec<StaleReason> {
+    let mut reasons = vec![];
+
+    if let Some(changed_ms) = key_changed_ms
+        && status.keys_loaded_ms.is_none_or(|loaded_ms| loaded_ms < changed_ms)
+    {
+        reasons.push(StaleReason::KeysNotReloaded {
+            changed_ms,
+            loaded_ms: status.keys_loaded_ms,
+        });
+    }
+
+    if !is_stale(status, now_ms) {
+        return reasons;
+    }
+
+    match status.recent_attempts.first() {
+        Some(attempt) if now_ms - attempt.time_ms > STALE_AFTER_MS => reasons.push(StaleReason::NotFetching {
+            last_attempt_ms: Some(attempt.time_ms),
+        }),
+        Some(attempt) if !attempt.succeeded() => reasons.push(match (attempt.http_status, &attempt.parse_error) {
+            (Some(status @ (401 | 403)), _) => StaleReason::KeyRejected {
+                status,
+                password_index: attempt.password_index,
+            },
+            (Some(429), _) => StaleReason::RateLimited {
+                password_index: attempt.password_index,
+            },
+            (_, Some(error)) => StaleReason::ParseFailed { error: error.clone() },
+            (status, None) => StaleReason::AgencyError {
+                status,
+                error: attempt.error.clone(),
+            },
+        }),
+        Some(_) => {}
+        None => reasons.push(StaleReason::NotFetching { last_attempt_ms: None }),
+    }
+
+    if status.last_success_ms.is_none() {
+        reasons.push(StaleReason::NeverSucceeded);
+    }
+
+    reasons
+}
+
+#[derive(Serialize, Clone, Deserialize, Debug, PartialEq)]
+pub struct FeedStatusSummary {
+    pub status: FeedIngestionStatus,
+    pub key_changed_ms: Option<i64>,
+    pub stale: bool,
+    pub reasons: Vec<StaleReason>,
+}
+
+#[cfg(feature = "ssr")]
+async fn load_ingestion_status_checked(
+    master_email: &str,
+    master_password: &str,
+) -> Result<Option<BTreeMap<String, FeedStatusSummary>>, ServerFnError> {
+    let operator = match crate::auth::authenticate_verified(master_email, master_password).await? {
+        Some(operator) => operator,
+        None => return Ok(None),
+    };
+
+    let response = match crate::birch::fetch_ingestion_status(&operator.birch_email, &operator.birch_password).await? {
+        Some(response) => response,
+        None => return Ok(None),
+    };
+
+    let last_changed = crate::history::last_changed()?;
+    let now_ms = chrono::Utc::now().timestamp_millis();
+
+    Ok(Some(
+        response
+            .feeds
+            .into_iter()
+            .map(|(feed_id, status)| {
+                let key_changed_ms = last_changed.get(&feed_id).copied();
+                let summary = FeedStatusSummary {
+                    stale: is_stale(&status, now_ms),
+                    reasons: explain(&status, key_changed_ms, now_ms),
+                    key_changed_ms,
+                    status,
+                };
+                (feed_id, summary)
+            })
+            .collect(),
+    ))
+}
+
+/// Open to every role, Viewers are there to watch feed health.
+#[server(endpoint = "load_ingestion_status")]
+pub async fn load_ingestion_status(
+    master_email: String,
+    master_password: String,
+) -> Result<Option<BTreeMap<String, FeedStatusSummary>>, ServerFnError> {
+    load_ingestion_status_checked(&master_email, &master_password).await
+}
+
+fn optional_time(i18n: I18nContext<Locale>, time_ms: Option<i64>, feed_id: String) -> impl IntoView {
+    match time_ms {
+        Some(time_ms) => view! { <Time time_ms=time_ms feed_id=feed_id/> }.into_any(),
+        None => view! { <span>{t!(i18n, stale_never)}</span> }.into_any(),
+    }
+}
+
+#[component]
+pub fn IngestionStatus() -> impl IntoView {
+    let i18n = use_i18n();
+
+    let query = use_query_map();
+
+    let (master_email, set_master_email) = signal(String::from(""));
+    let (master_password, set_master_password) = signal(String::from(""));
+    let (feed_filter, set_feed_filter) = signal(query.with_untracked(|x| x.get("feed_id")).unwrap_or_default());
+    let (stale_only, set_stale_only) = signal(false);
+
+    let feeds: RwSignal<Option<BTreeMap<String, FeedStatusSummary>>> = RwSignal::new(None);
+    let (message, set_message) = signal(String::from(""));
+
+    let reload = move || {
+        let (master_email, master_password) = (master_email.get(), master_password.get());
+
+        spawn_local(async move {
+            match load_ingestion_status(master_email, master_password).await {
+                Ok(Some(list)) => {
+                    let stale = list.values().filter(|x| x.stale).count();
+                    set_message.set(t_string!(i18n, ingestion_summary, count = list.len(), stale = stale).to_string());
+                    feeds.set(Some(list));
+                }
+                Ok(None) => set_message.set(t_string!(i18n, not_authorised).to_string()),
+                Err(err) => set_message.set(err.to_string()),
+            }
+        });
+    };
+
+    view! {
+        <PageMeta title=move || t_string!(i18n, nav_status) noindex=true/>
+        <Nav/>
+        <main class="p-8">
+            <h1 class="text-2xl font-bold text-tulip">{t!(i18n, nav_status)}</h1>
+            <p>{t!(i18n, ingestion_intro)}</p>
+
+            <form on:submit=move |event| {
+                event.prevent_default();
+                reload();
+            }>
+                <input
+                    type="email"
+                    placeholder=move || t_string!(i18n, email)
+                    prop:value=move || master_email.get()
+                    class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold mr-4"
+                    on:input=move |event| set_master_email.set(event_target_value(&event))
+                />
+                <input
+                    type="password"
+                    autocomplete="current-password"
+                    placeholder=move || t_string!(i18n, password)
+                    prop:value=move || master_password.get()
+                    class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold mr-4"
+                    on:input=move |event| set_master_password.set(event_target_value(&event))
+                />
+                <button type="submit" class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold">{t!(i18n, load)}</button>
+            </form>
+
+            <input
+                type="text"
+                placeholder=move || t_string!(i18n, ingestion_filter)
+                prop:value=move || feed_filter.get()
+                class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-2 text-lg font-bold mr-4"
+                on:input=move |event| set_feed_filter.set(event_target_value(&event))
+            />
+            <label>
+                <input type="checkbox" class="mr-2" prop:checked=move || stale_only.get() on:change=move |event| set_stale_only.set(event_target_checked(&event))/>
+                {t!(i18n, ingestion_stale_only)}
+            </label>
+
+            <p class="font-semibold">{move || message.get()}</p>
+
+            {
+                move || feeds.get().map(|list| view! {
+                    <ul class="my-4 space-y-6">
+                        {
+                            list.into_iter()
+                                .filter(|(feed_id, summary)| feed_id.contains(feed_filter.get().trim()) && (!stale_only.get() || summary.stale))
+                                .map(|(feed_id, summary)| view! {
+                                    <li>
+                                        <h2 class="text-lg font-semibold">
+                                            {feed_id.clone()}
+                                            <span class="ml-4">{move || if summary.stale { t_string!(i18n, ingestion_stale) } else { t_string!(i18n, ingestion_fresh) }}</span>
+                                        </h2>
+                                        <p>{t!(i18n, ingestion_last_success)}" "{optional_time(i18n, summary.status.last_success_ms, feed_id.clone())}</p>
+                                        <p>{t!(i18n, ingestion_last_failure)}" "{optional_time(i18n, summary.status.last_failure_ms, feed_id.clone())}</p>
+                                        <p>{t!(i18n, ingestion_keys_loaded)}" "{optional_time(i18n, summary.status.keys_loaded_ms, feed_id.clone())}</p>
+                                        <p>{t!(i18n, ingestion_keys_changed)}" "{optional_time(i18n, summary.key_changed_ms, feed_id.clone())}</p>
+                                        <ul class="list-disc ml-6 my-2">
+                                            {summary.reasons.iter().map(|reason| view! { <li>{reason.describe(i18n)}</li> }).collect_view()}
+                                        </ul>
+                                        <table class="text-sm my-2">
+                                            <thead>
+                                                <tr class="text-left">
+                                                    <th class="pr-4">{t!(i18n, ingestion_time)}</th>
+                                                    <th class="pr-4">{t!(i18n, ingestion_feed_type)}</th>
+                                                    <th class="pr-4">"HTTP"</th>
+                                                    <th class="pr-4">{t!(i18n, ingestion_bytes)}</th>
+                                                    <th class="pr-4">{t!(i18n, ingestion_password)}</th>
+                                                    <th>{t!(i18n, ingestion_error)}</th>
+                                                </tr>
+                                            </thead>
+                                            <tbody>
+                                                {
+                                                    summary.status.recent_attempts.into_iter().map(|attempt| view! {
+                                                        <tr>
+                                                            <td class="pr-4"><Time time_ms=attempt.time_ms feed_id=feed_id.clone()/></td>
+                                                            <td class="pr-4">{attempt.feed_type.clone()}</td>
+                                                            <td class="pr-4 font-mono">{attempt.http_status.map(|x| x.to_string()).unwrap_or_else(|| String::from("-"))}</td>
+                                                            <td class="pr-4 font-mono">{attempt.bytes}</td>
+                                                            <td class="pr-4 font-mono">{attempt.password_index.map(|x| format!("#{}", x)).unwrap_or_else(|| String::from("-"))}</td>
+                                                            <td>{attempt.parse_error.clone().or(attempt.error.clone()).unwrap_or_default()}</td>
+                                                        </tr>
+                                                    }).collect_view()
+                                                }
+                                            </tbody>
+                                        </table>
+                                    </li>
+                                }).collect_view()
+                        }
+                    </ul>
+                })
+            }
+        </main>
+    }
+}
@@ -0,0 +1,129 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// How often Alpenrose polls a feed, shared by the form and `submit_data` so both accept the same input.
+// Birch stores it as `fetch_interval_ms: Option<i32>`, `None` leaving it to Alpenrose's default.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Faster than this hammers agency servers.
+pub const MIN_FETCH_INTERVAL_MS: u32 = 1_000;
+/// Slower than this and the data is no longer realtime.
+pub const MAX_FETCH_INTERVAL_MS: u32 = 600_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FetchInterval {
+    /// Alpenrose's default.
+    Default,
+    Every { ms: u32 },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntervalError {
+    Unrecognised,
+    TooShort,
+    TooLong,
+}
+
+impl fmt::Display for IntervalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntervalError::Unrecognised => write!(f, "Interval must be a duration like 30s or 1500ms, or default"),
+            IntervalError::TooShort => write!(f, "Interval must be at least {}", format_ms(MIN_FETCH_INTERVAL_MS)),
+            IntervalError::TooLong => write!(f, "Interval must be at most {}", format_ms(MAX_FETCH_INTERVAL_MS)),
+        }
+    }
+}
+
+impl std::error::Error for IntervalError {}
+
+/// "1500ms", "30s" or "2m", whichever is exact.
+pub fn format_ms(ms: u32) -> String {
+    if ms != 0 && ms % 60_000 == 0 {
+        format!("{}m", ms / 60_000)
+    } else if ms != 0 && ms % 1_000 == 0 {
+        format!("{}s", ms / 1_000)
+    } else {
+        format!("{}ms", ms)
+    }
+}
+
+fn parse_duration_ms(text: &str) -> Option<f64> {
+    let (number, scale) = if let Some(number) = text.strip_suffix("ms") {
+        (number, 1.0)
+    } else if let Some(number) = text.strip_suffix('s') {
+        (number, 1_000.0)
+    } else if let Some(number) = text.strip_suffix('m') {
+        (number, 60_000.0)
+    } else {
+        // a bare number is milliseconds, as Birch stores it
+        (text, 1.0)
+    };
+
+    let number = number.trim().parse::<f64>().ok()?;
+
+    (number.is_finite() && number >= 0.0).then_some(number * scale)
+}
+
+impl FetchInterval {
+    /// Accepts "default", durations like "30s", "1.5s", "1500ms" or "2m", a bare number of milliseconds,
+    /// and the RON `None` / `Some(1000)` the form used to take.
+    pub fn parse(text: &str) -> Result<FetchInterval, IntervalError> {
+        let text = text.trim().to_lowercase();
+
+        if text == "default" || text == "none" {
+            return Ok(FetchInterval::Default);
+        }
+
+        let text = text
+            .strip_prefix("some(")
+            .and_then(|x| x.strip_suffix(')'))
+            .unwrap_or(&text);
+
+        let ms = parse_duration_ms(text).ok_or(IntervalError::Unrecognised)?.round();
+
+        if ms < MIN_FETCH_INTERVAL_MS as f64 {
+            Err(IntervalError::TooShort)
+        } else if ms > MAX_FETCH_INTERVAL_MS as f64 {
+            Err(IntervalError::TooLong)
+        } else {
+            Ok(FetchInterval::Every { ms: ms as u32 })
+        }
+    }
+
+    /// Birch's stored value, which is not bounds checked. Zero and negative values fall back to the default.
+    pub fn from_birch(fetch_interval_ms: Option<i32>) -> FetchInterval {
+        match fetch_interval_ms {
+            Some(ms) if ms > 0 => FetchInterval::Every { ms: ms as u32 },
+            _ => FetchInterval::Default,
+        }
+    }
+
+    pub fn to_birch(&self) -> Option<i32> {
+        match self {
+            FetchInterval::Default => None,
+            // within i32 since MAX_FETCH_INTERVAL_MS is
+            FetchInterval::Every { ms } => Some(*ms as i32),
+        }
+    }
+
+    /// What Alpenrose actually uses, given its default.
+    pub fn effective_ms(&self, default_ms: u32) -> u32 {
+        match self {
+            FetchInterval::Default => default_ms,
+            FetchInterval::Every { ms } => *ms,
+        }
+    }
+}
+
+/// The form's text for this interval, which `parse` reads back.
+impl fmt::Display for FetchInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchInterval::Default => write!(f, "default"),
+            FetchInterval::Every { ms } => write!(f, "{}", format_ms(*ms)),
+        }
+    }
+}
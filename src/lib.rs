@@ -1,4 +1,41 @@
 pub mod app;
+pub mod approvals;
+pub mod audit;
+pub mod auth;
+#[cfg(feature = "ssr")]
+pub mod birch;
+#[cfg(feature = "ssr")]
+pub mod config;
+pub mod diff;
+pub mod effective_config;
+pub mod feeds;
+pub mod gtfs_rt;
+#[cfg(feature = "ssr")]
+pub mod health;
+pub mod history;
+pub mod ingestion;
+pub mod interval;
+#[cfg(feature = "ssr")]
+pub mod metrics;
+#[cfg(feature = "ssr")]
+pub mod mock_birch;
+pub mod page_meta;
+#[cfg(feature = "ssr")]
+pub mod rate_limit;
+#[cfg(feature = "ssr")]
+pub mod robots;
+pub mod rotation;
+#[cfg(feature = "ssr")]
+pub mod security;
+#[cfg(feature = "ssr")]
+pub mod sitemap;
+#[cfg(feature = "ssr")]
+pub mod static_files;
+pub mod theme;
+pub mod time;
+pub mod users;
+
+leptos_i18n::load_locales!();
 
 #[cfg(feature = "hydrate")]
 #[wasm_bindgen::prelude::wasm_bindgen]
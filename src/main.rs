@@ -2,43 +2,14 @@
 // Catenary Transit Initiatives
 // Attribution cannot be removed
 
-#[cfg(feature = "ssr")]
-async fn robots(req: actix_web::HttpRequest) -> impl actix_web::Responder {
-    let banned_bots = vec![
-        "CCBot",
-        "ChatGPT-User",
-        "GPTBot",
-        "Google-Extended",
-        "anthropic-ai",
-        "ClaudeBot",
-        "Omgilibot",
-        "Omgili",
-        "FacebookBot",
-        "Diffbot",
-        "Bytespider",
-        "ImagesiftBot",
-        "cohere-ai",
-    ];
-
-    let robots_banned_bots = banned_bots
-        .into_iter()
-        .map(|x| format!("User-agent: {}\nDisallow: /", x))
-        .collect::<Vec<String>>()
-        .join("\n\n");
-
-    actix_web::HttpResponse::Ok()
-        .insert_header(("Content-Type", "text/plain"))
-        .body(robots_banned_bots)
-}
-
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     use actix_files::Files;
     use actix_web::*;
     use leptos::prelude::get_configuration;
     use leptos::*;
-    use leptos_actix::{LeptosRoutes, generate_route_list};
-    use leptos_meta::MetaTags;
+    use leptos_actix::{LeptosRoutes, generate_route_list, render_app_to_stream};
+    use leptos_router::Method;
     use tulip::app::*;
     // Setting this to None means we'll be using cargo-leptos and its env vars.
     let conf = get_configuration(None).unwrap();
@@ -47,6 +18,9 @@ async fn main() -> std::io::Result<()> {
 
     let addr = conf.leptos_options.site_addr;
 
+    // fail at startup rather than on the first request if tulip.ron does not parse
+    tulip::config::config();
+
     // Generate the list of routes in your Leptos App
     let routes = generate_route_list(App);
 
@@ -55,32 +29,34 @@ async fn main() -> std::io::Result<()> {
         let site_root = &leptos_options.site_root;
         let routes = &routes;
         actix_web::App::new()
-            .wrap(actix_block_ai_crawling::BlockAi)
-            .leptos_routes(routes.to_owned(),
-            {let leptos_options = leptos_options.clone();
-                 move || {
-                use leptos::prelude::*;
-                use leptos_meta::*;
-
-                view! {
-                    <!DOCTYPE html>
-                    <html lang="en">
-                        <head>
-                            <meta charset="utf-8" />
-                            <meta name="viewport" content="width=device-width, initial-scale=1" />
-                            <AutoReload options=leptos_options.clone() />
-                            <HydrationScripts options=leptos_options.clone()/>
-                            <MetaTags/>
-                        </head>
-                        <body><App />
-                        </body>
-                        </html>
-                }
-            }
+            .wrap(middleware::Condition::new(
+                tulip::config::config().robots.block_ai_crawlers,
+                actix_block_ai_crawling::BlockAi,
+            ))
+            .leptos_routes(routes.to_owned(), {
+                let leptos_options = leptos_options.clone();
+                move || shell(leptos_options.clone())
             })
-            .route("robots.txt", web::get().to(robots))
+            .route("robots.txt", web::get().to(tulip::robots::robots))
+            .route("/sitemap.xml", web::get().to(tulip::sitemap::sitemap))
+            .route("/sitemap-{index}.xml", web::get().to(tulip::sitemap::sitemap_page))
+            .route("/healthz", web::get().to(tulip::health::healthz))
+            .route("/readyz", web::get().to(tulip::health::readyz))
+            .route("/version", web::get().to(tulip::health::version))
+            .route("/metrics", web::get().to(tulip::metrics::metrics))
             .route("/api/{tail:.*}", leptos_actix::handle_server_fns())
-            .service(Files::new("/", site_root.to_string()))
+            // anything that is not a file renders the app, whose fallback route is the 404 page
+            .service(Files::new("/", site_root.to_string()).default_handler(render_app_to_stream(
+                {
+                    let leptos_options = leptos_options.clone();
+                    move || shell(leptos_options.clone())
+                },
+                Method::Get,
+            )))
+            .wrap(middleware::from_fn(tulip::rate_limit::limit_api_requests))
+            .wrap(middleware::from_fn(tulip::static_files::cache_headers))
+            .wrap(middleware::from_fn(tulip::security::security_headers))
+            .wrap(middleware::from_fn(tulip::metrics::record_requests))
             .wrap(middleware::Compress::default())
     })
     .bind(&addr)?
@@ -0,0 +1,188 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Prometheus metrics about Tulip itself, served on /metrics.
+// Routes are labelled by their pattern and server functions by their path, never by the raw
+// request path, so unknown URLs cannot grow the number of series.
+
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::{HttpRequest, HttpResponse};
+use prometheus::{
+    Encoder, HistogramVec, IntCounterVec, TextEncoder, register_histogram_vec,
+    register_int_counter_vec,
+};
+use std::collections::HashSet;
+use std::sync::LazyLock;
+use std::time::Instant;
+
+const LATENCY_BUCKETS: [f64; 12] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0,
+];
+
+static HTTP_REQUESTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "tulip_http_requests_total",
+        "HTTP requests by method, route or server function, and status",
+        &["method", "route", "status"]
+    )
+    .unwrap()
+});
+
+static HTTP_REQUEST_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "tulip_http_request_duration_seconds",
+        "Time until the response starts, by method and route or server function",
+        &["method", "route"],
+        LATENCY_BUCKETS.to_vec()
+    )
+    .unwrap()
+});
+
+static SSR_RENDER_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "tulip_ssr_render_seconds",
+        "Time until a server rendered page starts streaming, by route",
+        &["route"],
+        LATENCY_BUCKETS.to_vec()
+    )
+    .unwrap()
+});
+
+static BIRCH_CALLS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "tulip_birch_requests_total",
+        "Calls to Birch by endpoint and status, \"error\" when no response came back",
+        &["endpoint", "status"]
+    )
+    .unwrap()
+});
+
+static BIRCH_CALL_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "tulip_birch_request_duration_seconds",
+        "Time Birch took to answer, by endpoint",
+        &["endpoint"],
+        LATENCY_BUCKETS.to_vec()
+    )
+    .unwrap()
+});
+
+static LOGINS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "tulip_logins_total",
+        "Login attempts by outcome: success, failure or locked_out",
+        &["outcome"]
+    )
+    .unwrap()
+});
+
+static SERVER_FN_PATHS: LazyLock<HashSet<&'static str>> = LazyLock::new(|| {
+    leptos::server_fn::actix::server_fn_paths()
+        .map(|(path, _)| path)
+        .collect()
+});
+
+fn method_label(method: &actix_web::http::Method) -> &'static str {
+    use actix_web::http::Method;
+
+    match *method {
+        Method::GET => "GET",
+        Method::POST => "POST",
+        Method::HEAD => "HEAD",
+        Method::PUT => "PUT",
+        Method::DELETE => "DELETE",
+        Method::OPTIONS => "OPTIONS",
+        Method::PATCH => "PATCH",
+        _ => "other",
+    }
+}
+
+fn route_label(req: &HttpRequest) -> String {
+    match req.match_pattern() {
+        Some(pattern) if pattern.starts_with("/api/") => match SERVER_FN_PATHS.get(req.path()) {
+            Some(path) => path.to_string(),
+            None => String::from("/api/unknown"),
+        },
+        // actix_files is mounted at the root
+        Some(pattern) if pattern.is_empty() => String::from("static"),
+        Some(pattern) => pattern,
+        None => String::from("unmatched"),
+    }
+}
+
+/// Actix middleware counting and timing every request.
+pub async fn record_requests(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let started = Instant::now();
+    let method = method_label(req.method());
+
+    let res = next.call(req).await?;
+
+    let elapsed = started.elapsed().as_secs_f64();
+    let route = route_label(res.request());
+
+    HTTP_REQUESTS
+        .with_label_values(&[method, &route, res.status().as_str()])
+        .inc();
+    HTTP_REQUEST_DURATION
+        .with_label_values(&[method, &route])
+        .observe(elapsed);
+
+    let is_html = res
+        .headers()
+        .get(actix_web::http::header::CONTENT_TYPE)
+        .and_then(|x| x.to_str().ok())
+        .is_some_and(|x| x.starts_with("text/html"));
+
+    if is_html && route != "static" {
+        SSR_RENDER_DURATION.with_label_values(&[&route]).observe(elapsed);
+    }
+
+    Ok(res)
+}
+
+/// Records one call to Birch, `status` is `None` when the request failed without a response.
+pub fn observe_birch_call(endpoint: &str, status: Option<u16>, started: Instant) {
+    let status = status.map(|x| x.to_string()).unwrap_or_else(|| String::from("error"));
+
+    BIRCH_CALLS.with_label_values(&[endpoint, &status]).inc();
+    BIRCH_CALL_DURATION
+        .with_label_values(&[endpoint])
+        .observe(started.elapsed().as_secs_f64());
+}
+
+pub fn record_login(outcome: &str) {
+    LOGINS.with_label_values(&[outcome]).inc();
+}
+
+/// Serves every metric in the Prometheus text format.
+/// With `metrics_token` set, scrapers must send it as a bearer token.
+pub async fn metrics(req: HttpRequest) -> HttpResponse {
+    if let Some(token) = &crate::config::config().metrics_token {
+        let authorised = req
+            .headers()
+            .get(actix_web::http::header::AUTHORIZATION)
+            .and_then(|x| x.to_str().ok())
+            .and_then(|x| x.strip_prefix("Bearer "))
+            .is_some_and(|x| x == token);
+
+        if !authorised {
+            return HttpResponse::Unauthorized().finish();
+        }
+    }
+
+    let encoder = TextEncoder::new();
+    let mut buffer = vec![];
+
+    match encoder.encode(&prometheus::gather(), &mut buffer) {
+        Ok(()) => HttpResponse::Ok()
+            .content_type(encoder.format_type())
+            .body(buffer),
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
@@ -0,0 +1,363 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// A stand-in for Birch serving the endpoints Tulip uses, from a store seeded with sample feeds.
+// Alpenrose's resolved config is made up from each feed's overrides and example.com defaults,
+// and its ingestion status from canned fetch attempts. The mock's Alpenrose loads keys once at startup.
+// `cargo run --bin mock_birch --features ssr` starts it on 127.0.0.1:3100 (MOCK_BIRCH_ADDR),
+// point `birch_url` at it to run Tulip offline. Set MOCK_BIRCH_STORE to a RON file to keep changes between runs.
+
+use crate::app::{EachPasswordRow, KeyFormat, KeyResponse, PasswordFormat, PasswordInfo, RotationStrategy};
+use crate::effective_config::AlpenroseFeedConfig;
+use crate::ingestion::{FeedIngestionStatus, FetchAttempt, IngestionStatusResponse};
+use actix_web::{HttpRequest, HttpResponse, web};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// The accounts the mock accepts, as email and password.
+pub const ACCOUNTS: [(&str, &str); 2] = [
+    ("editor@example.com", "correct-horse-battery"),
+    ("viewer@example.com", "viewer-password"),
+];
+
+/// /setrealtimekey always answers 500 for this feed, to exercise Tulip's error handling.
+pub const BROKEN_FEED_ID: &str = "f-broken~rt";
+
+const FEED_TYPES: [&str; 3] = ["vehicle", "trip", "alert"];
+
+/// The interval the mock's Alpenrose uses for feeds without one.
+pub const DEFAULT_FETCH_INTERVAL_MS: u32 = 10_000;
+
+pub struct MockBirch {
+    feeds: Mutex<BTreeMap<String, EachPasswordRow>>,
+    store_file: Option<PathBuf>,
+    /// When the mock's Alpenrose loaded the keys, it never reloads them.
+    keys_loaded_ms: i64,
+}
+
+pub fn sample_feeds() -> BTreeMap<String, EachPasswordRow> {
+    BTreeMap::from([
+        (
+            String::from("f-test~rt"),
+            EachPasswordRow {
+                passwords: Some(PasswordFormat {
+                    key_formats: vec![KeyFormat::Header(String::from("api_key"))],
+                    passwords: vec![PasswordInfo {
+                        password: vec![String::from("e2e-secret-key")],
+                        creator_email: String::from("editor@example.com"),
+                        ..Default::default()
+                    }],
+                    override_schedule_url: None,
+                    override_realtime_vehicle_positions: None,
+                    override_realtime_trip_updates: None,
+                    override_alerts: None,
+                    rotation: RotationStrategy::RoundRobin,
+                }),
+                fetch_interval_ms: Some(5000),
+            },
+        ),
+        (
+            String::from("f-dqc-wmata~rt"),
+            EachPasswordRow {
+                passwords: crate::app::give_wmata_format(),
+                fetch_interval_ms: Some(10000),
+            },
+        ),
+        (
+            String::from("f-9-bay~area~rt"),
+            EachPasswordRow {
+                passwords: crate::app::give_sfbay_format(),
+                fetch_interval_ms: None,
+            },
+        ),
+        (
+            String::from(BROKEN_FEED_ID),
+            EachPasswordRow {
+                passwords: None,
+                fetch_interval_ms: None,
+            },
+        ),
+    ])
+}
+
+impl MockBirch {
+    pub fn seeded() -> MockBirch {
+        MockBirch {
+            feeds: Mutex::new(sample_feeds()),
+            store_file: None,
+            keys_loaded_ms: chrono::Utc::now().timestamp_millis(),
+        }
+    }
+
+    /// Loads the feeds from `path`, or seeds it with the sample feeds if it does not exist yet.
+    pub fn from_file(path: PathBuf) -> Result<MockBirch, String> {
+        let feeds = match std::fs::read_to_string(&path) {
+            Ok(text) => ron::from_str(&text).map_err(|err| format!("{} does not parse: {}", path.display(), err))?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => sample_feeds(),
+            Err(err) => return Err(format!("Could not read {}: {}", path.display(), err)),
+        };
+
+        let mock = MockBirch {
+            feeds: Mutex::new(BTreeMap::new()),
+            store_file: Some(path),
+            keys_loaded_ms: chrono::Utc::now().timestamp_millis(),
+        };
+
+        mock.save(&feeds);
+        *mock.feeds.lock().unwrap() = feeds;
+
+        Ok(mock)
+    }
+
+    fn save(&self, feeds: &BTreeMap<String, EachPasswordRow>) {
+        if let Some(path) = &self.store_file {
+            let text = ron::ser::to_string_pretty(feeds, ron::ser::PrettyConfig::default()).unwrap();
+
+            if let Err(err) = std::fs::write(path, text) {
+                eprintln!("Could not write {}: {}", path.display(), err);
+            }
+        }
+    }
+}
+
+fn authorised(email: Option<&str>, password: Option<&str>) -> bool {
+    ACCOUNTS
+        .iter()
+        .any(|(account_email, account_password)| email == Some(*account_email) && password == Some(*account_password))
+}
+
+#[derive(Deserialize)]
+struct Credentials {
+    email: String,
+    password: String,
+}
+
+async fn get_realtime_keys(mock: web::Data<MockBirch>, form: web::Form<Credentials>) -> HttpResponse {
+    if !authorised(Some(&form.email), Some(&form.password)) {
+        return HttpResponse::Unauthorized().body("Unauthorized");
+    }
+
+    let passwords = mock.feeds.lock().unwrap().clone();
+
+    HttpResponse::Ok().json(KeyResponse { passwords })
+}
+
+async fn set_realtime_key(
+    mock: web::Data<MockBirch>,
+    req: HttpRequest,
+    feed_id: web::Path<String>,
+    body: String,
+) -> HttpResponse {
+    let header = |name: &str| req.headers().get(name).and_then(|x| x.to_str().ok());
+
+    if !authorised(header("email"), header("password")) {
+        return HttpResponse::Unauthorized().body("Unauthorized");
+    }
+
+    let feed_id = feed_id.into_inner();
+
+    if feed_id == BROKEN_FEED_ID {
+        return HttpResponse::InternalServerError().body("Internal Server Error");
+    }
+
+    let row: EachPasswordRow = match ron::from_str(&body) {
+        Ok(row) => row,
+        Err(err) => return HttpResponse::BadRequest().body(err.to_string()),
+    };
+
+    let mut feeds = mock.feeds.lock().unwrap();
+    feeds.insert(feed_id, row);
+    mock.save(&feeds);
+
+    HttpResponse::Ok().body("OK")
+}
+
+/// Overrides where the feed has them, otherwise made up default URLs for the feed id.
+pub fn alpenrose_config(feed_id: &str, row: &EachPasswordRow) -> AlpenroseFeedConfig {
+    let format = row.passwords.as_ref();
+    let default_url = |feed_type: &str| Some(format!("https://agency.example.com/{}/{}", feed_id, feed_type));
+
+    AlpenroseFeedConfig {
+        schedule_url: format
+            .and_then(|x| x.override_schedule_url.clone())
+            .or_else(|| default_url("gtfs.zip")),
+        realtime_vehicle_positions: format
+            .and_then(|x| x.override_realtime_vehicle_positions.clone())
+            .or_else(|| default_url("vehicles")),
+        realtime_trip_updates: format
+            .and_then(|x| x.override_realtime_trip_updates.clone())
+            .or_else(|| default_url("trips")),
+        realtime_alerts: format.and_then(|x| x.override_alerts.clone()).or_else(|| default_url("alerts")),
+        fetch_interval_ms: match row.fetch_interval_ms {
+            Some(ms) if ms > 0 => ms as u32,
+            _ => DEFAULT_FETCH_INTERVAL_MS,
+        },
+        key_formats: format.map(|x| x.key_formats.clone()).unwrap_or_default(),
+        password_count: format.map(|x| x.passwords.len()).unwrap_or_default(),
+    }
+}
+
+async fn get_realtime_config(mock: web::Data<MockBirch>, req: HttpRequest, feed_id: web::Path<String>) -> HttpResponse {
+    let header = |name: &str| req.headers().get(name).and_then(|x| x.to_str().ok());
+
+    if !authorised(header("email"), header("password")) {
+        return HttpResponse::Unauthorized().body("Unauthorized");
+    }
+
+    match mock.feeds.lock().unwrap().get(feed_id.as_str()) {
+        Some(row) => HttpResponse::Ok().json(alpenrose_config(&feed_id, row)),
+        None => HttpResponse::NotFound().body("No such feed"),
+    }
+}
+
+fn attempt(time_ms: i64, http_status: u16, parse_error: Option<&str>, password_index: Option<usize>) -> FetchAttempt {
+    FetchAttempt {
+        time_ms,
+        feed_type: String::from("vehicle"),
+        http_status: Some(http_status),
+        bytes: if http_status == 200 { 2048 } else { 0 },
+        parse_error: parse_error.map(String::from),
+        error: None,
+        password_index,
+    }
+}
+
+/// Canned attempts, 10 seconds apart: the broken feed is rejected, the Bay Area feed is rate limited
+/// on its second password, WMATA sends data that does not parse, and the rest are healthy.
+pub fn ingestion_status(feed_id: &str, row: &EachPasswordRow, keys_loaded_ms: i64, now_ms: i64) -> FeedIngestionStatus {
+    let first_password = row.passwords.as_ref().filter(|x| !x.passwords.is_empty()).map(|_| 0);
+    let times = [now_ms - 2_000, now_ms - 12_000, now_ms - 22_000];
+
+    let recent_attempts = match feed_id {
+        BROKEN_FEED_ID => times.map(|time_ms| attempt(time_ms, 401, None, None)).to_vec(),
+        "f-9-bay~area~rt" => vec![
+            attempt(times[0], 429, None, Some(1)),
+            attempt(times[1], 200, None, Some(0)),
+            attempt(times[2], 200, None, Some(0)),
+        ],
+        "f-dqc-wmata~rt" => times
+            .map(|time_ms| attempt(time_ms, 200, Some("invalid wire type 7 at offset 12"), first_password))
+            .to_vec(),
+        _ => times.map(|time_ms| attempt(time_ms, 200, None, first_password)).to_vec(),
+    };
+
+    FeedIngestionStatus {
+        last_success_ms: match feed_id {
+            BROKEN_FEED_ID => None,
+            "f-dqc-wmata~rt" => Some(now_ms - 3_600_000),
+            _ => recent_attempts.iter().find(|x| x.succeeded()).map(|x| x.time_ms),
+        },
+        last_failure_ms: recent_attempts.iter().find(|x| !x.succeeded()).map(|x| x.time_ms),
+        keys_loaded_ms: Some(keys_loaded_ms),
+        recent_attempts,
+    }
+}
+
+async fn get_realtime_status(mock: web::Data<MockBirch>, req: HttpRequest) -> HttpResponse {
+    let header = |name: &str| req.headers().get(name).and_then(|x| x.to_str().ok());
+
+    if !authorised(header("email"), header("password")) {
+        return HttpResponse::Unauthorized().body("Unauthorized");
+    }
+
+    let now_ms = chrono::Utc::now().timestamp_millis();
+
+    let feeds = mock
+        .feeds
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(feed_id, row)| (feed_id.clone(), ingestion_status(feed_id, row, mock.keys_loaded_ms, now_ms)))
+        .collect();
+
+    HttpResponse::Ok().json(IngestionStatusResponse { feeds })
+}
+
+#[derive(Deserialize)]
+struct GtfsRtQuery {
+    feed_id: String,
+    feed_type: String,
+    format: Option<String>,
+}
+
+/// An empty GTFS-RT FeedMessage, for the json and ron formats.
+#[derive(Serialize)]
+struct FeedMessage {
+    header: FeedHeader,
+    entity: Vec<serde_json::Value>,
+}
+
+#[derive(Serialize)]
+struct FeedHeader {
+    gtfs_realtime_version: String,
+    incrementality: String,
+    timestamp: u64,
+}
+
+fn encode_varint(mut value: u64, out: &mut Vec<u8>) {
+    while value >= 0x80 {
+        out.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// The same empty FeedMessage as protobuf, encoded by hand to avoid a protobuf dependency.
+fn feed_message_protobuf(timestamp: u64) -> Vec<u8> {
+    // FeedHeader, gtfs_realtime_version = 1, incrementality = 2, timestamp = 3
+    let mut header = vec![0x0a, 3];
+    header.extend(b"2.0");
+    header.extend([0x10, 0, 0x18]);
+    encode_varint(timestamp, &mut header);
+
+    // FeedMessage, header = 1
+    let mut message = vec![0x0a];
+    encode_varint(header.len() as u64, &mut message);
+    message.extend(header);
+    message
+}
+
+async fn gtfs_rt(mock: web::Data<MockBirch>, query: web::Query<GtfsRtQuery>) -> HttpResponse {
+    if !mock.feeds.lock().unwrap().contains_key(&query.feed_id) || !FEED_TYPES.contains(&query.feed_type.as_str()) {
+        return HttpResponse::NotFound().body("No such feed");
+    }
+
+    let timestamp = chrono::Utc::now().timestamp() as u64;
+
+    let message = FeedMessage {
+        header: FeedHeader {
+            gtfs_realtime_version: String::from("2.0"),
+            incrementality: String::from("FULL_DATASET"),
+            timestamp,
+        },
+        entity: vec![],
+    };
+
+    match query.format.as_deref() {
+        Some("json") => HttpResponse::Ok().json(message),
+        Some("ron") => HttpResponse::Ok()
+            .content_type("text/plain; charset=utf-8")
+            .body(ron::ser::to_string_pretty(&message, ron::ser::PrettyConfig::default()).unwrap()),
+        _ => HttpResponse::Ok()
+            .content_type("application/x-protobuf")
+            .body(feed_message_protobuf(timestamp)),
+    }
+}
+
+async fn index() -> HttpResponse {
+    HttpResponse::Ok().body("Mock Birch")
+}
+
+/// Mount with `App::new().app_data(web::Data::new(mock)).configure(routes)`.
+pub fn routes(config: &mut web::ServiceConfig) {
+    config
+        .route("/", web::get().to(index))
+        .route("/getrealtimekeys", web::post().to(get_realtime_keys))
+        .route("/setrealtimekey/{feed_id}/", web::post().to(set_realtime_key))
+        .route("/getrealtimeconfig/{feed_id}/", web::get().to(get_realtime_config))
+        .route("/getrealtimestatus", web::get().to(get_realtime_status))
+        .route("/gtfs_rt", web::get().to(gtfs_rt));
+}
@@ -0,0 +1,41 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Title, description and crawler tags for each page, rendered on the server by leptos_meta.
+
+use leptos::prelude::*;
+use leptos::text_prop::TextProp;
+use leptos_meta::{Meta, Title};
+
+/// Public pages get a description and OpenGraph tags, `noindex` pages are kept out of search indexes.
+#[component]
+pub fn PageMeta(
+    #[prop(into)] title: TextProp,
+    #[prop(optional, into)] description: Option<TextProp>,
+    /// Operator pages, also disallowed in robots.txt, and error pages.
+    #[prop(optional)]
+    noindex: bool,
+) -> impl IntoView {
+    let full_title = TextProp::from(move || format!("{} · Tulip", title.get()));
+
+    let public_tags = (!noindex).then(|| {
+        view! {
+            <Meta property="og:site_name" content="Tulip"/>
+            <Meta property="og:type" content="website"/>
+            <Meta property="og:title" content=full_title.clone()/>
+            {
+                description.map(|description| view! {
+                    <Meta name="description" content=description.clone()/>
+                    <Meta property="og:description" content=description/>
+                })
+            }
+        }
+    });
+
+    view! {
+        <Title text=full_title.clone()/>
+        {noindex.then(|| view! { <Meta name="robots" content="noindex, nofollow"/> })}
+        {public_tags}
+    }
+}
@@ -0,0 +1,200 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Throttling for the server functions, which all take credentials.
+// Each IP gets a fixed number of /api/ requests per minute, and each account is locked out
+// for exponentially longer after repeated failed logins.
+
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use leptos::prelude::ServerFnError;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    pub requests_per_minute_per_ip: u32,
+    /// Failed logins allowed before the account is locked out.
+    pub free_failed_logins: u32,
+    /// The first lockout, doubled for each further failure.
+    pub lockout_base_seconds: u64,
+    pub lockout_max_seconds: u64,
+    /// Use X-Forwarded-For / Forwarded to find the client IP. Only enable behind a proxy that sets them.
+    pub trust_proxy_headers: bool,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig {
+            requests_per_minute_per_ip: 60,
+            free_failed_logins: 3,
+            lockout_base_seconds: 5,
+            lockout_max_seconds: 900,
+            trust_proxy_headers: false,
+        }
+    }
+}
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Accounts and IPs tracked before old entries are pruned.
+pub const MAX_TRACKED: usize = 10_000;
+
+struct IpWindow {
+    started: Instant,
+    requests: u32,
+}
+
+struct FailedLogins {
+    failures: u32,
+    last_failed: Instant,
+    locked_until: Option<Instant>,
+}
+
+static IP_WINDOWS: LazyLock<Mutex<HashMap<String, IpWindow>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+static FAILED_LOGINS: LazyLock<Mutex<HashMap<String, FailedLogins>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn account_key(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/// Counts a request from this IP, returning how long to wait if it is over the limit.
+fn take_ip_request(ip: &str) -> Option<Duration> {
+    let limit = crate::config::config().rate_limit.requests_per_minute_per_ip;
+    let mut windows = IP_WINDOWS.lock().unwrap();
+
+    if windows.len() > MAX_TRACKED {
+        windows.retain(|_, window| window.started.elapsed() < WINDOW);
+    }
+
+    let window = windows.entry(ip.to_string()).or_insert(IpWindow {
+        started: Instant::now(),
+        requests: 0,
+    });
+
+    if window.started.elapsed() >= WINDOW {
+        window.started = Instant::now();
+        window.requests = 0;
+    }
+
+    window.requests += 1;
+
+    match window.requests > limit {
+        true => Some(WINDOW.saturating_sub(window.started.elapsed())),
+        false => None,
+    }
+}
+
+/// Actix middleware limiting each IP's calls to /api/, where the server functions live.
+pub async fn limit_api_requests(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, actix_web::Error> {
+    if !req.path().starts_with("/api/") {
+        return next.call(req).await.map(ServiceResponse::map_into_left_body);
+    }
+
+    let ip = {
+        let connection_info = req.connection_info();
+        match crate::config::config().rate_limit.trust_proxy_headers {
+            true => connection_info.realip_remote_addr().map(|x| x.to_string()),
+            false => connection_info.peer_addr().map(|x| x.to_string()),
+        }
+        .unwrap_or_default()
+    };
+
+    match take_ip_request(&ip) {
+        None => next.call(req).await.map(ServiceResponse::map_into_left_body),
+        Some(retry_after) => {
+            println!("Rate limited {} on {}", ip, req.path());
+
+            // server functions decode a "ServerError|" body into a readable ServerFnError
+            let response = actix_web::HttpResponse::TooManyRequests()
+                .insert_header(("Retry-After", retry_after.as_secs().max(1).to_string()))
+                .body(format!(
+                    "ServerError|Too many requests, try again in {} seconds",
+                    retry_after.as_secs().max(1)
+                ));
+
+            Ok(req.into_response(response).map_into_right_body())
+        }
+    }
+}
+
+/// Fails while the account is locked out after too many failed logins.
+pub fn check_account(email: &str) -> Result<(), ServerFnError> {
+    let failed_logins = FAILED_LOGINS.lock().unwrap();
+
+    match failed_logins.get(&account_key(email)).and_then(|x| x.locked_until) {
+        Some(locked_until) if locked_until > Instant::now() => Err(ServerFnError::new(format!(
+            "Too many failed logins for this account, try again in {} seconds",
+            (locked_until - Instant::now()).as_secs().max(1)
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Failures are forgotten once the longest lockout has passed without another one.
+fn forget_after() -> Duration {
+    Duration::from_secs(crate::config::config().rate_limit.lockout_max_seconds).max(WINDOW)
+}
+
+/// Keeps the map bounded when many different emails fail, dropping forgotten accounts first,
+/// then the ones that failed longest ago.
+fn prune_failed_logins(failed_logins: &mut HashMap<String, FailedLogins>) {
+    if failed_logins.len() < MAX_TRACKED {
+        return;
+    }
+
+    let forget_after = forget_after();
+    failed_logins.retain(|_, x| x.last_failed.elapsed() < forget_after);
+
+    if failed_logins.len() >= MAX_TRACKED {
+        let mut last_failed = failed_logins.values().map(|x| x.last_failed).collect::<Vec<_>>();
+        last_failed.sort_unstable();
+        let cutoff = last_failed[last_failed.len() - MAX_TRACKED / 2];
+        failed_logins.retain(|_, x| x.last_failed >= cutoff);
+    }
+}
+
+pub fn record_failed_login(email: &str) {
+    let config = &crate::config::config().rate_limit;
+    let mut failed_logins = FAILED_LOGINS.lock().unwrap();
+
+    prune_failed_logins(&mut failed_logins);
+
+    let entry = failed_logins.entry(account_key(email)).or_insert(FailedLogins {
+        failures: 0,
+        last_failed: Instant::now(),
+        locked_until: None,
+    });
+
+    if entry.last_failed.elapsed() >= forget_after() {
+        entry.failures = 0;
+    }
+
+    entry.failures += 1;
+    entry.last_failed = Instant::now();
+
+    if entry.failures > config.free_failed_logins {
+        let doublings = (entry.failures - config.free_failed_logins - 1).min(32);
+        let seconds = config
+            .lockout_base_seconds
+            .saturating_mul(1u64 << doublings)
+            .min(config.lockout_max_seconds);
+
+        println!("Locking out {} for {} seconds after {} failed logins", email, seconds, entry.failures);
+
+        entry.locked_until = Some(Instant::now() + Duration::from_secs(seconds));
+    }
+}
+
+pub fn record_successful_login(email: &str) {
+    FAILED_LOGINS.lock().unwrap().remove(&account_key(email));
+}
@@ -0,0 +1,132 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// robots.txt, built from the deployment's crawler policy.
+
+use serde::Deserialize;
+
+/// Pages and endpoints for operators, kept out of every crawler's reach when `disallow_admin_paths` is set.
+pub const ADMIN_PATHS: [&str; 8] = [
+    "/realtimekeys",
+    "/users",
+    "/audit",
+    "/history",
+    "/approvals",
+    "/status",
+    "/test1",
+    "/api/",
+];
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct RobotsRule {
+    pub user_agent: String,
+    #[serde(default)]
+    pub allow: Vec<String>,
+    #[serde(default)]
+    pub disallow: Vec<String>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct RobotsConfig {
+    /// Turns actix_block_ai_crawling's BlockAi middleware on, which refuses known AI crawlers outright.
+    pub block_ai_crawlers: bool,
+    /// User agents told to stay off the whole site.
+    pub banned_bots: Vec<String>,
+    /// Per user agent allow and disallow rules, "*" for every other crawler.
+    pub rules: Vec<RobotsRule>,
+    /// Adds ADMIN_PATHS to the disallow rules of every crawler not already banned.
+    pub disallow_admin_paths: bool,
+    /// Absolute URL of the sitemap to advertise.
+    pub sitemap_url: Option<String>,
+}
+
+impl Default for RobotsConfig {
+    fn default() -> Self {
+        RobotsConfig {
+            block_ai_crawlers: true,
+            banned_bots: [
+                "CCBot",
+                "ChatGPT-User",
+                "GPTBot",
+                "Google-Extended",
+                "anthropic-ai",
+                "ClaudeBot",
+                "Omgilibot",
+                "Omgili",
+                "FacebookBot",
+                "Diffbot",
+                "Bytespider",
+                "ImagesiftBot",
+                "cohere-ai",
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+            rules: vec![],
+            disallow_admin_paths: true,
+            sitemap_url: None,
+        }
+    }
+}
+
+/// Crawlers only follow the most specific group matching them, so the admin paths are repeated
+/// in every group and a "*" group is added if the config has none.
+pub fn robots_txt(config: &RobotsConfig) -> String {
+    let mut groups = config
+        .banned_bots
+        .iter()
+        .map(|bot| format!("User-agent: {}\nDisallow: /", bot))
+        .collect::<Vec<String>>();
+
+    let mut rules = config.rules.clone();
+
+    if config.disallow_admin_paths && !rules.iter().any(|rule| rule.user_agent == "*") {
+        rules.push(RobotsRule {
+            user_agent: String::from("*"),
+            allow: vec![],
+            disallow: vec![],
+        });
+    }
+
+    for rule in rules {
+        let mut lines = vec![format!("User-agent: {}", rule.user_agent)];
+
+        let mut disallow = rule.disallow;
+
+        if config.disallow_admin_paths {
+            for path in ADMIN_PATHS {
+                if !disallow.iter().any(|x| x == path) {
+                    disallow.push(path.to_string());
+                }
+            }
+        }
+
+        lines.extend(rule.allow.iter().map(|path| format!("Allow: {}", path)));
+        lines.extend(disallow.iter().map(|path| format!("Disallow: {}", path)));
+
+        // an empty group allows everything, which robots.txt spells as an empty Disallow
+        if lines.len() == 1 {
+            lines.push(String::from("Disallow:"));
+        }
+
+        groups.push(lines.join("\n"));
+    }
+
+    let mut robots = groups.join("\n\n");
+
+    if let Some(sitemap_url) = &config.sitemap_url {
+        robots.push_str(&format!("\n\nSitemap: {}", sitemap_url));
+    }
+
+    robots.push('\n');
+
+    robots
+}
+
+pub async fn robots() -> actix_web::HttpResponse {
+    actix_web::HttpResponse::Ok()
+        .insert_header(("Content-Type", "text/plain"))
+        .body(robots_txt(&crate::config::config().robots))
+}
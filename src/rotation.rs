@@ -0,0 +1,624 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Key rotation: mark the old key as rotating, add the new one alongside it,
+// probe the agency with the new key, then retire the old one.
+
+use crate::app::{EachPasswordRow, KeyStatus, PasswordFormat, PasswordInfo, RotationStrategy};
+#[cfg(feature = "ssr")]
+use crate::app::{KeyFormat, REDACTED_PASSWORD};
+use crate::effective_config::AlpenroseFeedConfig;
+use crate::gtfs_rt::FeedTimestamps;
+use crate::i18n::*;
+use crate::time::Time;
+use chrono::{Days, NaiveDate, Utc};
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use leptos_i18n::I18nContext;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::IpAddr;
+
+#[derive(Serialize, Clone, Deserialize, Debug)]
+pub struct ProbeResult {
+    /// The URL without the key, so it is safe to show.
+    pub url: String,
+    pub status: Option<u16>,
+    pub bytes: usize,
+    pub error: Option<String>,
+    /// When the response was a GTFS-rt FeedMessage, the times it carries, to tell a live feed from a frozen one.
+    pub feed: Option<FeedTimestamps>,
+}
+
+impl ProbeResult {
+    pub fn succeeded(&self) -> bool {
+        self.status == Some(200)
+    }
+}
+
+/// The realtime URLs a key can be probed against: the form's overrides,
+/// otherwise the URLs Alpenrose uses for the feed.
+pub fn probe_urls(format: &PasswordFormat, alpenrose: Option<&AlpenroseFeedConfig>) -> Vec<String> {
+    [
+        (&format.override_realtime_vehicle_positions, alpenrose.and_then(|x| x.realtime_vehicle_positions.as_ref())),
+        (&format.override_realtime_trip_updates, alpenrose.and_then(|x| x.realtime_trip_updates.as_ref())),
+        (&format.override_alerts, alpenrose.and_then(|x| x.realtime_alerts.as_ref())),
+    ]
+    .into_iter()
+    .filter_map(|(override_url, alpenrose_url)| override_url.as_ref().or(alpenrose_url))
+    .cloned()
+    .collect()
+}
+
+/// Whether Tulip may fetch from this address. Probe URLs come from operators,
+/// so loopback, private, link-local and similar addresses are refused to keep them off Tulip's own network.
+pub fn is_public_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || a == 0
+                // shared address space, 100.64.0.0/10
+                || (a == 100 && (64..128).contains(&b)))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_address(IpAddr::V4(ip)),
+            None => {
+                let first = ip.segments()[0];
+
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    // unique local, fc00::/7
+                    || (first & 0xfe00) == 0xfc00
+                    // link-local, fe80::/10
+                    || (first & 0xffc0) == 0xfe80)
+            }
+        },
+    }
+}
+
+/// Checks a probe URL is http(s) on a public address, returning the address to connect to
+/// so a second DNS lookup cannot point the request somewhere else.
+#[cfg(feature = "ssr")]
+async fn public_target(url: &str) -> Result<(reqwest::Url, std::net::SocketAddr), ServerFnError> {
+    let parsed = reqwest::Url::parse(url).map_err(|err| ServerFnError::new(format!("{} is not a valid URL, {}", url, err)))?;
+
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(ServerFnError::new(format!("{} is not an http or https URL", url)));
+    }
+
+    let addresses = {
+        let parsed = parsed.clone();
+        actix_web::web::block(move || parsed.socket_addrs(|| None))
+            .await?
+            .map_err(|err| ServerFnError::new(format!("Could not resolve {}, {}", url, err)))?
+    };
+
+    match addresses.iter().find(|x| !is_public_address(x.ip())) {
+        Some(address) => Err(ServerFnError::new(format!(
+            "{} resolves to {}, which is not a public address",
+            url,
+            address.ip()
+        ))),
+        None => match addresses.first() {
+            Some(address) => Ok((parsed, *address)),
+            None => Err(ServerFnError::new(format!("Could not resolve {}", url))),
+        },
+    }
+}
+
+impl RotationStrategy {
+    /// As written in the RON password field.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RotationStrategy::RoundRobin => "RoundRobin",
+            RotationStrategy::Failover => "Failover",
+            RotationStrategy::Random => "Random",
+            RotationStrategy::Weighted => "Weighted",
+        }
+    }
+}
+
+fn strategy_label(i18n: I18nContext<Locale>, strategy: RotationStrategy) -> &'static str {
+    match strategy {
+        RotationStrategy::RoundRobin => t_string!(i18n, rotation_round_robin),
+        RotationStrategy::Failover => t_string!(i18n, rotation_failover),
+        RotationStrategy::Random => t_string!(i18n, rotation_random),
+        RotationStrategy::Weighted => t_string!(i18n, rotation_weighted),
+    }
+}
+
+fn strategy_description(i18n: I18nContext<Locale>, strategy: RotationStrategy) -> &'static str {
+    match strategy {
+        RotationStrategy::RoundRobin => t_string!(i18n, rotation_round_robin_description),
+        RotationStrategy::Failover => t_string!(i18n, rotation_failover_description),
+        RotationStrategy::Random => t_string!(i18n, rotation_random_description),
+        RotationStrategy::Weighted => t_string!(i18n, rotation_weighted_description),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RotationError {
+    /// Weighted rotation, and this password has no weight.
+    MissingWeight(usize),
+    /// Weighted rotation, and every weight is 0.
+    AllWeightsZero,
+    /// This password has a weight the strategy does not use.
+    UnusedWeight { index: usize, strategy: RotationStrategy },
+}
+
+impl fmt::Display for RotationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RotationError::MissingWeight(index) => {
+                write!(f, "Weighted rotation needs a weight on every password, #{} has none", index)
+            }
+            RotationError::AllWeightsZero => write!(f, "Weighted rotation needs at least one weight above 0"),
+            RotationError::UnusedWeight { index, strategy } => write!(
+                f,
+                "Weights are only used by Weighted rotation, remove the weight from #{} or change {:?} to Weighted",
+                index, strategy
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RotationError {}
+
+/// The form's translation of a `RotationError`, the server answers with its `Display`.
+pub fn describe_rotation_error(i18n: I18nContext<Locale>, err: RotationError) -> String {
+    match err {
+        RotationError::MissingWeight(index) => t_string!(i18n, rotation_missing_weight, index = index).to_string(),
+        RotationError::AllWeightsZero => t_string!(i18n, rotation_all_weights_zero).to_string(),
+        RotationError::UnusedWeight { index, strategy } => {
+            t_string!(i18n, rotation_unused_weight, index = index, strategy = strategy_label(i18n, strategy)).to_string()
+        }
+    }
+}
+
+/// Checks the feed's rotation strategy can be used with its passwords.
+pub fn validate_rotation(format: &PasswordFormat) -> Result<(), RotationError> {
+    let weights = format.passwords.iter().map(|x| x.weight).collect::<Vec<_>>();
+
+    match format.rotation {
+        RotationStrategy::Weighted => {
+            if let Some(index) = weights.iter().position(|x| x.is_none()) {
+                return Err(RotationError::MissingWeight(index));
+            }
+
+            if !weights.iter().any(|x| x.unwrap_or(0) > 0) {
+                return Err(RotationError::AllWeightsZero);
+            }
+
+            Ok(())
+        }
+        strategy => match weights.iter().position(|x| x.is_some()) {
+            Some(index) => Err(RotationError::UnusedWeight { index, strategy }),
+            None => Ok(()),
+        },
+    }
+}
+
+/// Weights are kept only while the strategy is Weighted, starting at 1.
+fn set_rotation(format: &mut PasswordFormat, rotation: RotationStrategy) {
+    format.rotation = rotation;
+
+    for password_info in format.passwords.iter_mut() {
+        password_info.weight = match rotation {
+            RotationStrategy::Weighted => Some(password_info.weight.unwrap_or(1)),
+            _ => None,
+        };
+    }
+}
+
+fn set_weight(format: &mut PasswordFormat, index: usize, weight: u32) {
+    if let Some(password_info) = format.passwords.get_mut(index) {
+        password_info.weight = Some(weight);
+    }
+}
+
+#[cfg(feature = "ssr")]
+async fn probe_key_checked(
+    master_email: &str,
+    master_password: &str,
+    feed_id: &str,
+    password: &str,
+    index: usize,
+) -> Result<Option<Vec<ProbeResult>>, ServerFnError> {
+    let operator = match crate::auth::authenticate_verified(master_email, master_password).await? {
+        Some(operator) => operator,
+        None => return Ok(None),
+    };
+
+    crate::auth::require_role(&operator, crate::auth::Role::KeyEditor)?;
+
+    let format = match ron::from_str::<Option<PasswordFormat>>(password)? {
+        Some(format) => format,
+        None => return Err(ServerFnError::new("This feed has no keys to probe")),
+    };
+
+    let password_info = match format.passwords.get(index) {
+        Some(password_info) => password_info,
+        None => return Err(ServerFnError::new(format!("There is no key {}", index))),
+    };
+
+    if password_info.password.len() != format.key_formats.len() {
+        return Err(ServerFnError::new("The key must be the same length as key_formats"));
+    }
+
+    if password_info.password.iter().any(|x| x == REDACTED_PASSWORD) {
+        return Err(ServerFnError::new("Reveal the key before probing it"));
+    }
+
+    // a feed Alpenrose does not know yet can still be probed at its overrides
+    let alpenrose =
+        match crate::birch::fetch_effective_config(&operator.birch_email, &operator.birch_password, feed_id).await {
+            Ok(Some(alpenrose)) => Some(alpenrose),
+            Ok(None) => return Ok(None),
+            Err(err) => {
+                println!("No Alpenrose config to probe {} with, {}", feed_id, err);
+                None
+            }
+        };
+
+    let urls = probe_urls(&format, alpenrose.as_ref());
+
+    if urls.is_empty() {
+        return Err(ServerFnError::new("This feed has no realtime URLs to probe"));
+    }
+
+    let mut results = vec![];
+
+    for url in urls {
+        let (parsed, address) = match public_target(&url).await {
+            Ok(target) => target,
+            Err(err) => {
+                results.push(ProbeResult { url, status: None, bytes: 0, error: Some(err.to_string()), feed: None });
+                continue;
+            }
+        };
+
+        // pinned to the checked address, and redirects could lead anywhere
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(10))
+            .redirect(reqwest::redirect::Policy::none())
+            .resolve(parsed.host_str().unwrap_or_default(), address)
+            .build()?;
+
+        let mut request = client.get(parsed);
+
+        for (key_format, value) in format.key_formats.iter().zip(password_info.password.iter()) {
+            request = match key_format {
+                KeyFormat::Header(name) => request.header(name, value),
+                KeyFormat::UrlQuery(name) => request.query(&[(name, value)]),
+            };
+        }
+
+        results.push(match request.send().await {
+            Ok(response) => {
+                let status = response.status().as_u16();
+                match response.bytes().await {
+                    Ok(bytes) => ProbeResult {
+                        url,
+                        status: Some(status),
+                        bytes: bytes.len(),
+                        error: None,
+                        feed: crate::gtfs_rt::feed_timestamps(&bytes),
+                    },
+                    Err(err) => ProbeResult { url, status: Some(status), bytes: 0, error: Some(err.to_string()), feed: None },
+                }
+            }
+            Err(err) => ProbeResult { url, status: None, bytes: 0, error: Some(err.without_url().to_string()), feed: None },
+        });
+    }
+
+    Ok(Some(results))
+}
+
+/// Fetches each realtime URL of the feed with one key from the submission form.
+#[server(endpoint = "probe_key")]
+pub async fn probe_key(
+    master_email: String,
+    master_password: String,
+    feed_id: String,
+    password: String,
+    index: usize,
+) -> Result<Option<Vec<ProbeResult>>, ServerFnError> {
+    use crate::audit::{AuditAction, AuditEntry, AuditOutcome};
+
+    let mut entry = AuditEntry::new(&master_email, AuditAction::ProbeKey, Some(feed_id.clone()));
+    let result = probe_key_checked(&master_email, &master_password, feed_id.trim(), &password, index).await;
+    entry.outcome = AuditOutcome::from_option(&result);
+    crate::audit::record(entry)?;
+
+    result
+}
+
+fn set_status(format: &mut PasswordFormat, index: usize, status: KeyStatus) {
+    if let Some(password_info) = format.passwords.get_mut(index) {
+        password_info.status = status;
+    }
+}
+
+/// Shows each key in the submission form with buttons for the rotation steps.
+/// Every step edits the form, nothing reaches Birch until the form is submitted.
+#[component]
+pub fn RotationPanel(
+    master_email: Signal<String>,
+    master_password: Signal<String>,
+    form_feed_id: ReadSignal<String>,
+    form_password: ReadSignal<String>,
+    set_form_password: WriteSignal<String>,
+) -> impl IntoView {
+    let i18n = use_i18n();
+
+    let (probe_message, set_probe_message) = signal(String::from(""));
+    let probe_results: RwSignal<Vec<ProbeResult>> = RwSignal::new(vec![]);
+    let (weight_message, set_weight_message) = signal(String::from(""));
+
+    let modify = move |change: &dyn Fn(&mut PasswordFormat)| {
+        if let Ok(Some(mut format)) = ron::from_str::<Option<PasswordFormat>>(&form_password.get_untracked()) {
+            change(&mut format);
+            set_form_password.set(
+                ron::ser::to_string_pretty(&Some(format), ron::ser::PrettyConfig::default()).unwrap(),
+            );
+        }
+    };
+
+    view! {
+        <h3 class="text-lg font-semibold">{t!(i18n, rotation_title)}</h3>
+        <p>{t!(i18n, rotation_intro)}</p>
+        {
+            move || match ron::from_str::<Option<PasswordFormat>>(&form_password.get()) {
+                Ok(Some(format)) => {
+                    let has_active = format.passwords.iter().any(|x| x.status == KeyStatus::Active);
+                    let rotation = format.rotation;
+
+                    view! {
+                        <p class="my-2">
+                            {t!(i18n, rotation_strategy)}" "
+                            <select
+                                class="bg-gray dark:bg-darksky rounded-md p-1 px-2 border-2 border-tulip mr-4"
+                                on:change=move |event| {
+                                    let value = event_target_value(&event);
+                                    if let Some(strategy) = RotationStrategy::ALL.into_iter().find(|x| x.as_str() == value) {
+                                        modify(&|format| set_rotation(format, strategy));
+                                    }
+                                }
+                            >
+                                {
+                                    RotationStrategy::ALL.into_iter().map(|strategy| view! {
+                                        <option value=strategy.as_str() selected=strategy == rotation>{move || strategy_label(i18n, strategy)}</option>
+                                    }).collect_view()
+                                }
+                            </select>
+                            <span>{move || strategy_description(i18n, rotation)}</span>
+                        </p>
+                        {
+                            validate_rotation(&format).err().map(|err| view! { <p>{move || format!("❌ {}", describe_rotation_error(i18n, err))}</p> })
+                        }
+                        <p>{move || weight_message.get()}</p>
+                        <ul class="my-2">
+                            {
+                                format.passwords.iter().enumerate().map(|(index, password_info)| {
+                                    let status = password_info.status;
+                                    view! {
+                                        <li class="my-1">
+                                            <span class="font-mono mr-4">{format!("#{} {:?}", index, status)}</span>
+                                            <span class="mr-4">{password_info.creator_email.clone()}</span>
+                                            <span class="mr-4">{password_info.expires_at.map(|x| t_string!(i18n, rotation_expires, date = x).to_string()).unwrap_or_default()}</span>
+                                            {
+                                                (rotation == RotationStrategy::Weighted).then(|| view! {
+                                                    <span class="mr-4">
+                                                        {t!(i18n, rotation_weight)}" "
+                                                        <input
+                                                            type="number"
+                                                            min="0"
+                                                            prop:value=password_info.weight.unwrap_or_default().to_string()
+                                                            class="bg-gray dark:bg-darksky rounded-md p-1 px-2 border-2 border-tulip w-20"
+                                                            on:change=move |event| {
+                                                                match event_target_value(&event).trim().parse::<u32>() {
+                                                                    Ok(weight) => {
+                                                                        set_weight_message.set(String::new());
+                                                                        modify(&|format| set_weight(format, index, weight));
+                                                                    }
+                                                                    Err(_) => set_weight_message.set(
+                                                                        t_string!(i18n, rotation_weight_invalid, index = index).to_string(),
+                                                                    ),
+                                                                }
+                                                            }
+                                                        />
+                                                    </span>
+                                                })
+                                            }
+                                            {
+                                                (status == KeyStatus::Active).then(|| view! {
+                                                    <button class="underline text-blue-500 dark:text-blue-300 mr-2"
+                                                    on:click=move |_| modify(&|format| set_status(format, index, KeyStatus::Rotating))
+                                                    >{t!(i18n, rotation_mark_rotating)}</button>
+                                                })
+                                            }
+                                            {
+                                                (status != KeyStatus::Rotating).then(|| view! {
+                                                    <button class="underline text-blue-500 dark:text-blue-300 mr-2"
+                                                    on:click=move |_| {
+                                                        let (master_email, master_password, feed_id, password) = (master_email.get(), master_password.get(), form_feed_id.get(), form_password.get());
+                                                        set_probe_message.set(t_string!(i18n, rotation_probing).to_string());
+                                                        probe_results.set(vec![]);
+                                                        spawn_local(async move {
+                                                            match probe_key(master_email, master_password, feed_id, password, index).await {
+                                                                Ok(Some(results)) => {
+                                                                    let all_succeeded = results.iter().all(|x| x.succeeded());
+                                                                    set_probe_message.set(String::new());
+                                                                    probe_results.set(results);
+                                                                    if all_succeeded {
+                                                                        modify(&|format| set_status(format, index, KeyStatus::Active));
+                                                                    }
+                                                                }
+                                                                Ok(None) => set_probe_message.set(t_string!(i18n, not_authorised).to_string()),
+                                                                Err(err) => set_probe_message.set(err.to_string()),
+                                                            }
+                                                        });
+                                                    }
+                                                    >{t!(i18n, rotation_probe)}</button>
+                                                })
+                                            }
+                                            {
+                                                (status == KeyStatus::Rotating && has_active).then(|| view! {
+                                                    <button class="underline text-blue-500 dark:text-blue-300 mr-2"
+                                                    on:click=move |_| modify(&|format| if index < format.passwords.len() { format.passwords.remove(index); })
+                                                    >{t!(i18n, rotation_retire)}</button>
+                                                })
+                                            }
+                                        </li>
+                                    }
+                                }).collect_view()
+                            }
+                        </ul>
+                        <button class="bg-gray dark:bg-darksky rounded-md p-1 px-2 border-2 border-tulip text-sm font-bold"
+                        on:click=move |_| {
+                            let creator_email = master_email.get_untracked();
+                            modify(&|format| {
+                                let key_count = format.key_formats.len();
+                                format.passwords.push(PasswordInfo {
+                                    password: vec![String::new(); key_count],
+                                    creator_email: creator_email.clone(),
+                                    created_at: Some(Utc::now().date_naive()),
+                                    status: KeyStatus::Unverified,
+                                    weight: (format.rotation == RotationStrategy::Weighted).then_some(1),
+                                    ..Default::default()
+                                });
+                            });
+                        }
+                        >{t!(i18n, rotation_add_key)}</button>
+                    }.into_any()
+                }
+                _ => view! { <p>{t!(i18n, rotation_needs_password)}</p> }.into_any(),
+            }
+        }
+        <pre class="font-mono text-wrap">{move || probe_message.get()}</pre>
+        <ul>
+            {
+                move || probe_results.get().into_iter().map(|result| {
+                    let feed_id = form_feed_id.get_untracked();
+                    view! {
+                        <li class="my-1">
+                            <p class="font-mono break-all">
+                                {t_string!(i18n, rotation_probe_result,
+                                    url = &result.url,
+                                    status = result.status.map(|x| x.to_string()).unwrap_or_default(),
+                                    bytes = result.bytes,
+                                    error = result.error.clone().unwrap_or_default()).to_string()}
+                            </p>
+                            {
+                                result.feed.map(|feed| view! {
+                                    {feed.header_ms.map(|time_ms| view! {
+                                        <p class="ml-4">{t!(i18n, rotation_probe_header)}" "<Time time_ms=time_ms feed_id=feed_id.clone()/></p>
+                                    })}
+                                    {feed.newest_vehicle_ms.zip(feed.oldest_vehicle_ms).map(|(newest_ms, oldest_ms)| view! {
+                                        <p class="ml-4">
+                                            {t_string!(i18n, rotation_probe_vehicles, count = feed.vehicles).to_string()}" "
+                                            <Time time_ms=newest_ms feed_id=feed_id.clone()/>
+                                            ", "{t!(i18n, rotation_probe_oldest_vehicle)}" "
+                                            <Time time_ms=oldest_ms feed_id=feed_id.clone()/>
+                                        </p>
+                                    })}
+                                })
+                            }
+                        </li>
+                    }
+                }).collect_view()
+            }
+        </ul>
+    }
+}
+
+/// Every key expiring within the chosen number of days, soonest first.
+#[component]
+pub fn ExpiringKeys(keys: RwSignal<BTreeMap<String, EachPasswordRow>>) -> impl IntoView {
+    let i18n = use_i18n();
+
+    let (days_text, set_days_text) = signal(String::from("30"));
+    let days_valid = move || days_text.with(|x| x.trim().parse::<u64>().is_ok());
+    // a typo keeps the last valid number of days
+    let days = Memo::new(move |previous: Option<&u64>| {
+        days_text.with(|x| x.trim().parse().ok()).unwrap_or(previous.copied().unwrap_or(30))
+    });
+
+    let expiring = move || {
+        let today = Utc::now().date_naive();
+        let until = today.checked_add_days(Days::new(days.get())).unwrap_or(NaiveDate::MAX);
+
+        let mut expiring = keys.with(|keys| {
+            keys.iter()
+                .flat_map(|(feed_id, row)| {
+                    row.passwords.iter().flat_map(move |format| {
+                        format.passwords.iter().enumerate().filter_map(move |(index, password_info)| {
+                            password_info
+                                .expires_at
+                                .filter(|expires_at| *expires_at <= until)
+                                .map(|expires_at| (expires_at, feed_id.clone(), index, password_info.clone()))
+                        })
+                    })
+                })
+                .collect::<Vec<_>>()
+        });
+
+        expiring.sort_by_key(|x| x.0);
+
+        expiring
+            .into_iter()
+            .map(|(expires_at, feed_id, index, password_info)| view! {
+                <tr>
+                    <td class="pr-8 font-mono">{feed_id}</td>
+                    <td class="pr-8">{format!("#{}", index)}</td>
+                    <td class="pr-8">
+                        {match expires_at < today {
+                            true => t_string!(i18n, expiring_expired, date = expires_at).to_string(),
+                            false => expires_at.to_string(),
+                        }}
+                    </td>
+                    <td class="pr-8">{password_info.creator_email}</td>
+                    <td class="pr-8">{password_info.agency_contact.unwrap_or_default()}</td>
+                    <td>{password_info.notes.unwrap_or_default()}</td>
+                </tr>
+            })
+            .collect_view()
+    };
+
+    view! {
+        <h2 class="text-xl font-semibold">{t!(i18n, expiring_title)}</h2>
+        <p>
+            {t!(i18n, expiring_in_the_next)}" "
+            <input
+                type="number"
+                min="0"
+                prop:value=move || days_text.get()
+                class="bg-gray dark:bg-darksky rounded-md p-1 px-2 border-2 border-tulip w-24"
+                on:input=move |event| set_days_text.set(event_target_value(&event))
+            />
+            " "{t!(i18n, expiring_days)}
+        </p>
+        {move || (!days_valid()).then(|| view! { <p>{t!(i18n, expiring_days_invalid)}</p> })}
+        <table class="my-4">
+            <tr class="text-left">
+                <th class="pr-8">{t!(i18n, expiring_feed)}</th>
+                <th class="pr-8">{t!(i18n, expiring_key)}</th>
+                <th class="pr-8">{t!(i18n, expiring_expires)}</th>
+                <th class="pr-8">{t!(i18n, expiring_creator)}</th>
+                <th class="pr-8">{t!(i18n, expiring_agency_contact)}</th>
+                <th>{t!(i18n, expiring_notes)}</th>
+            </tr>
+            {expiring}
+        </table>
+    }
+}
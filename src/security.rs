@@ -0,0 +1,152 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Security headers for every response.
+// Pages rendered by Leptos get a Content Security Policy carrying a per-request nonce, which
+// Leptos also puts on its hydration scripts. Everything else gets the same policy without one.
+
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::middleware::Next;
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct SecurityHeadersConfig {
+    /// Strict-Transport-Security max-age, 0 leaves the header out.
+    pub hsts_max_age_seconds: u64,
+    pub frame_options: String,
+    pub referrer_policy: String,
+    pub permissions_policy: String,
+    /// Sources added to the matching CSP directives, e.g. a CDN or an analytics endpoint.
+    pub extra_script_src: Vec<String>,
+    pub extra_style_src: Vec<String>,
+    pub extra_font_src: Vec<String>,
+    pub extra_img_src: Vec<String>,
+    pub extra_connect_src: Vec<String>,
+    /// Send Content-Security-Policy-Report-Only instead, to try a policy out without breaking pages.
+    pub csp_report_only: bool,
+}
+
+impl Default for SecurityHeadersConfig {
+    fn default() -> Self {
+        SecurityHeadersConfig {
+            hsts_max_age_seconds: 31536000,
+            frame_options: String::from("DENY"),
+            referrer_policy: String::from("no-referrer"),
+            permissions_policy: String::from(
+                "camera=(), microphone=(), geolocation=(), payment=(), usb=()",
+            ),
+            extra_script_src: vec![],
+            extra_style_src: vec![],
+            extra_font_src: vec![],
+            extra_img_src: vec![],
+            extra_connect_src: vec![],
+            csp_report_only: false,
+        }
+    }
+}
+
+fn directive(name: &str, sources: &[&str], extra: &[String]) -> String {
+    let mut directive = String::from(name);
+
+    for source in sources.iter().copied().chain(extra.iter().map(|x| x.as_str())) {
+        directive.push(' ');
+        directive.push_str(source);
+    }
+
+    directive
+}
+
+/// Builds the Content-Security-Policy value.
+/// `nonce` is the per-request nonce of server rendered pages; without one no inline script may run.
+pub fn content_security_policy(config: &SecurityHeadersConfig, nonce: Option<&str>) -> String {
+    let nonce_source = nonce.map(|nonce| format!("'nonce-{}'", nonce));
+
+    let mut script_sources = vec!["'self'", "'wasm-unsafe-eval'"];
+    if let Some(nonce_source) = &nonce_source {
+        script_sources.push(nonce_source);
+    }
+
+    [
+        String::from("default-src 'self'"),
+        directive("script-src", &script_sources, &config.extra_script_src),
+        directive("style-src", &["'self'"], &config.extra_style_src),
+        // inline style attributes, scripts stay nonce only
+        String::from("style-src-attr 'unsafe-inline'"),
+        directive("font-src", &["'self'"], &config.extra_font_src),
+        directive("img-src", &["'self'", "data:"], &config.extra_img_src),
+        directive("connect-src", &["'self'"], &config.extra_connect_src),
+        String::from("object-src 'none'"),
+        String::from("base-uri 'self'"),
+        String::from("form-action 'self'"),
+        String::from("frame-ancestors 'none'"),
+    ]
+    .join("; ")
+}
+
+fn csp_header_name(config: &SecurityHeadersConfig) -> HeaderName {
+    match config.csp_report_only {
+        true => actix_web::http::header::CONTENT_SECURITY_POLICY_REPORT_ONLY,
+        false => actix_web::http::header::CONTENT_SECURITY_POLICY,
+    }
+}
+
+/// Creates this request's nonce for Leptos and sends the matching Content-Security-Policy.
+/// Call from the shell, before `HydrationScripts` renders.
+pub fn provide_csp_nonce() {
+    use leptos::nonce::{provide_nonce, use_nonce};
+
+    provide_nonce();
+
+    let config = &crate::config::config().security_headers;
+
+    if let (Some(nonce), Some(response_options)) = (use_nonce(), leptos::prelude::use_context::<leptos_actix::ResponseOptions>())
+        && let Ok(value) = HeaderValue::from_str(&content_security_policy(config, Some(&nonce)))
+    {
+        response_options.insert_header(csp_header_name(config), value);
+    }
+}
+
+/// Actix middleware adding the security headers a response does not already have.
+pub async fn security_headers(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    use actix_web::http::header;
+
+    let mut res = next.call(req).await?;
+
+    let config = &crate::config::config().security_headers;
+
+    let mut headers = vec![
+        (csp_header_name(config), content_security_policy(config, None)),
+        (header::X_FRAME_OPTIONS, config.frame_options.clone()),
+        (header::REFERRER_POLICY, config.referrer_policy.clone()),
+        (HeaderName::from_static("permissions-policy"), config.permissions_policy.clone()),
+        (header::X_CONTENT_TYPE_OPTIONS, String::from("nosniff")),
+    ];
+
+    if config.hsts_max_age_seconds > 0 {
+        headers.push((
+            header::STRICT_TRANSPORT_SECURITY,
+            format!("max-age={}; includeSubDomains", config.hsts_max_age_seconds),
+        ));
+    }
+
+    let response_headers = res.headers_mut();
+
+    for (name, value) in headers {
+        if response_headers.contains_key(&name) {
+            continue;
+        }
+
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            response_headers.insert(name, value);
+        }
+    }
+
+    Ok(res)
+}
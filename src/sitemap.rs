@@ -0,0 +1,156 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// sitemap.xml for the pages anyone may read: "/", "/help" and a /feed/{feed_id} page per feed in Birch.
+// Sitemaps hold at most 50,000 URLs, beyond that /sitemap.xml becomes an index of /sitemap-{n}.xml files.
+// Birch does not give Tulip routes or stops, so there are no pages for them to list.
+
+use crate::feeds::PublicFeed;
+use actix_web::{HttpRequest, HttpResponse, web};
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+
+pub const MAX_URLS_PER_SITEMAP: usize = 50_000;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SitemapEntry {
+    pub path: String,
+    pub lastmod: Option<NaiveDate>,
+}
+
+/// A page per feed, dated by the feed's last successful fetch so crawlers come back to live feeds.
+pub fn feed_entries(feeds: &BTreeMap<String, PublicFeed>) -> Vec<SitemapEntry> {
+    feeds
+        .values()
+        .map(|feed| SitemapEntry {
+            path: format!("/feed/{}", feed.feed_id),
+            lastmod: feed
+                .last_success_ms
+                .and_then(chrono::DateTime::from_timestamp_millis)
+                .map(|x| x.date_naive()),
+        })
+        .collect()
+}
+
+/// Every public page, admin pages never appear even if listed here by mistake.
+/// Without Birch, the feed pages are left out rather than failing the whole sitemap.
+pub async fn public_entries() -> Vec<SitemapEntry> {
+    let feeds = match crate::feeds::public_feeds().await {
+        Ok(feeds) => feed_entries(&feeds),
+        Err(err) => {
+            println!("Sitemap lists no feed pages, {}", err);
+            vec![]
+        }
+    };
+
+    // static pages change when Tulip is deployed
+    ["/", "/help"]
+        .into_iter()
+        .map(|path| SitemapEntry {
+            path: path.to_string(),
+            lastmod: crate::health::build_time().map(|x| x.date_naive()),
+        })
+        .chain(feeds)
+        .filter(|entry| {
+            !crate::robots::ADMIN_PATHS
+                .iter()
+                .any(|admin_path| entry.path.starts_with(admin_path))
+        })
+        .collect()
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn url_element(tag: &str, base_url: &str, path: &str, lastmod: Option<NaiveDate>) -> String {
+    let lastmod = lastmod
+        .map(|x| format!("<lastmod>{}</lastmod>", x.format("%Y-%m-%d")))
+        .unwrap_or_default();
+
+    format!(
+        "<{tag}><loc>{}</loc>{}</{tag}>",
+        escape_xml(&format!("{}{}", base_url, path)),
+        lastmod
+    )
+}
+
+pub fn sitemap_xml(base_url: &str, entries: &[SitemapEntry]) -> String {
+    let urls = entries
+        .iter()
+        .map(|entry| url_element("url", base_url, &entry.path, entry.lastmod))
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n{}\n</urlset>\n",
+        urls
+    )
+}
+
+/// Lists /sitemap-0.xml onwards, each file's lastmod is the newest of its entries.
+pub fn sitemap_index_xml(base_url: &str, entries: &[SitemapEntry]) -> String {
+    let sitemaps = entries
+        .chunks(MAX_URLS_PER_SITEMAP)
+        .enumerate()
+        .map(|(index, chunk)| {
+            let lastmod = chunk.iter().filter_map(|entry| entry.lastmod).max();
+            url_element("sitemap", base_url, &format!("/sitemap-{}.xml", index), lastmod)
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<sitemapindex xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n{}\n</sitemapindex>\n",
+        sitemaps
+    )
+}
+
+/// `public_url` from the config, otherwise the scheme and host this request came in on.
+fn base_url(req: &HttpRequest) -> String {
+    match &crate::config::config().public_url {
+        Some(public_url) => public_url.trim_end_matches('/').to_string(),
+        None => {
+            let connection_info = req.connection_info();
+            format!("{}://{}", connection_info.scheme(), connection_info.host())
+        }
+    }
+}
+
+fn xml_response(body: String) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("application/xml; charset=utf-8")
+        .body(body)
+}
+
+/// /sitemap.xml, a plain sitemap or an index once there are too many URLs for one.
+pub fn root_sitemap_xml(base_url: &str, entries: &[SitemapEntry]) -> String {
+    match entries.len() > MAX_URLS_PER_SITEMAP {
+        true => sitemap_index_xml(base_url, entries),
+        false => sitemap_xml(base_url, entries),
+    }
+}
+
+/// /sitemap-{index}.xml, which only exists while /sitemap.xml is an index.
+pub fn sitemap_page_xml(base_url: &str, entries: &[SitemapEntry], index: usize) -> Option<String> {
+    match entries.chunks(MAX_URLS_PER_SITEMAP).nth(index) {
+        Some(chunk) if entries.len() > MAX_URLS_PER_SITEMAP => Some(sitemap_xml(base_url, chunk)),
+        _ => None,
+    }
+}
+
+pub async fn sitemap(req: HttpRequest) -> HttpResponse {
+    xml_response(root_sitemap_xml(&base_url(&req), &public_entries().await))
+}
+
+pub async fn sitemap_page(req: HttpRequest, index: web::Path<usize>) -> HttpResponse {
+    match sitemap_page_xml(&base_url(&req), &public_entries().await, index.into_inner()) {
+        Some(body) => xml_response(body),
+        None => HttpResponse::NotFound().finish(),
+    }
+}
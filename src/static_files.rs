@@ -0,0 +1,46 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Cache-Control for the files actix_files serves from the site root.
+// Vendored fonts and images rarely change and may be cached for a while; the wasm bundle keeps
+// its name across builds, so browsers revalidate it against the ETag actix_files sends.
+
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{CACHE_CONTROL, HeaderValue};
+use actix_web::middleware::Next;
+
+const VENDORED_MAX_AGE: &str = "public, max-age=2592000";
+
+/// The Cache-Control for a static path, `None` for pages and server functions.
+pub fn cache_control(path: &str) -> Option<&'static str> {
+    if path.starts_with("/fonts/") || path.starts_with("/images/") {
+        Some(VENDORED_MAX_AGE)
+    } else if path.starts_with("/pkg/") {
+        Some("no-cache")
+    } else if path.ends_with(".svg") {
+        Some(VENDORED_MAX_AGE)
+    } else {
+        None
+    }
+}
+
+/// Actix middleware adding Cache-Control to successful static file responses.
+pub async fn cache_headers(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let cache_control = cache_control(req.path());
+
+    let mut res = next.call(req).await?;
+
+    if let Some(cache_control) = cache_control
+        && res.status().is_success()
+        && !res.headers().contains_key(CACHE_CONTROL)
+    {
+        res.headers_mut().insert(CACHE_CONTROL, HeaderValue::from_static(cache_control));
+    }
+
+    Ok(res)
+}
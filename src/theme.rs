@@ -0,0 +1,148 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Light, dark or system theme, kept in a cookie so the server renders the right class on <html>.
+// "dark" and "light" force a theme, no class lets the stylesheet follow prefers-color-scheme.
+
+use crate::i18n::*;
+use leptos::prelude::*;
+
+pub const COOKIE_NAME: &str = "theme";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Theme {
+    Light,
+    Dark,
+    #[default]
+    System,
+}
+
+impl Theme {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+            Theme::System => "system",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Theme> {
+        [Theme::Light, Theme::Dark, Theme::System].into_iter().find(|x| x.as_str() == text)
+    }
+
+    /// The class on <html>, empty to follow the system.
+    pub fn html_class(&self) -> &'static str {
+        match self {
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+            Theme::System => "",
+        }
+    }
+
+    pub fn next(&self) -> Theme {
+        match self {
+            Theme::Light => Theme::Dark,
+            Theme::Dark => Theme::System,
+            Theme::System => Theme::Light,
+        }
+    }
+
+    /// Material Symbols icon name.
+    fn icon(&self) -> &'static str {
+        match self {
+            Theme::Light => "light_mode",
+            Theme::Dark => "dark_mode",
+            Theme::System => "brightness_auto",
+        }
+    }
+}
+
+/// The theme in the request's cookie, for the shell and the first render.
+#[cfg(feature = "ssr")]
+pub fn theme_from_request() -> Theme {
+    use_context::<actix_web::HttpRequest>()
+        .and_then(|req| req.cookie(COOKIE_NAME))
+        .and_then(|cookie| Theme::parse(cookie.value()))
+        .unwrap_or_default()
+}
+
+/// After hydration the class the server put on <html> is the source of truth.
+#[cfg(not(feature = "ssr"))]
+fn theme_from_document() -> Theme {
+    let class_list = match document().document_element() {
+        Some(html) => html.class_list(),
+        None => return Theme::default(),
+    };
+
+    if class_list.contains("dark") {
+        Theme::Dark
+    } else if class_list.contains("light") {
+        Theme::Light
+    } else {
+        Theme::System
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct ThemeSetting(pub RwSignal<Theme>);
+
+/// Call once from `App`.
+pub fn provide_theme() {
+    #[cfg(feature = "ssr")]
+    let theme = theme_from_request();
+    #[cfg(not(feature = "ssr"))]
+    let theme = theme_from_document();
+
+    provide_context(ThemeSetting(RwSignal::new(theme)));
+}
+
+/// Only the browser has a document to change.
+#[cfg(feature = "hydrate")]
+fn apply_theme(theme: Theme) {
+    use wasm_bindgen::JsCast;
+
+    if let Some(html) = document().document_element() {
+        let class_list = html.class_list();
+        let _ = class_list.remove_2("light", "dark");
+
+        if !theme.html_class().is_empty() {
+            let _ = class_list.add_1(theme.html_class());
+        }
+    }
+
+    if let Ok(html_document) = document().dyn_into::<web_sys::HtmlDocument>() {
+        let _ = html_document.set_cookie(&format!(
+            "{}={}; Path=/; Max-Age=31536000; SameSite=Lax",
+            COOKIE_NAME,
+            theme.as_str()
+        ));
+    }
+}
+
+/// Cycles light, dark and system.
+#[component]
+pub fn ThemeToggle() -> impl IntoView {
+    let i18n = use_i18n();
+    let ThemeSetting(theme) = expect_context::<ThemeSetting>();
+
+    view! {
+        <button
+            type="button"
+            class="material-symbols-outlined"
+            title=move || match theme.get() {
+                Theme::Light => t_string!(i18n, theme_light),
+                Theme::Dark => t_string!(i18n, theme_dark),
+                Theme::System => t_string!(i18n, theme_system),
+            }
+            on:click=move |_| {
+                let next = theme.get_untracked().next();
+                theme.set(next);
+                #[cfg(feature = "hydrate")]
+                apply_theme(next);
+            }
+        >
+            {move || theme.get().icon()}
+        </button>
+    }
+}
@@ -0,0 +1,198 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Timestamps shown as relative time plus an absolute time in the zone the viewer picked.
+// The server only knows UTC, so pages render in UTC first and switch to the chosen zone,
+// and start counting relative time, once hydrated. Agency zones come from agency_time_zones in tulip.ron,
+// loaded the first time the viewer picks agency time.
+
+use crate::i18n::*;
+use chrono::{DateTime, Local, Utc};
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use leptos_i18n::I18nContext;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+const STORAGE_KEY: &str = "time_zone";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TimeZoneChoice {
+    /// The browser's own zone.
+    Viewer,
+    Utc,
+    /// The feed's agency zone, where a timestamp belongs to a feed with one.
+    Agency,
+}
+
+impl TimeZoneChoice {
+    pub const ALL: [TimeZoneChoice; 3] = [TimeZoneChoice::Viewer, TimeZoneChoice::Utc, TimeZoneChoice::Agency];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimeZoneChoice::Viewer => "viewer",
+            TimeZoneChoice::Utc => "utc",
+            TimeZoneChoice::Agency => "agency",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<TimeZoneChoice> {
+        TimeZoneChoice::ALL.into_iter().find(|x| x.as_str() == text)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct TimePreferences {
+    pub zone: RwSignal<TimeZoneChoice>,
+    /// Ticks every second once hydrated, for relative times.
+    pub now_ms: RwSignal<i64>,
+    pub hydrated: RwSignal<bool>,
+    /// Feed id to IANA zone, `None` until agency time is first picked.
+    pub agency_time_zones: RwSignal<Option<BTreeMap<String, String>>>,
+}
+
+/// Each feed's agency zone from tulip.ron. Zones chrono-tz does not know are left out.
+#[server(endpoint = "load_agency_time_zones")]
+pub async fn load_agency_time_zones() -> Result<BTreeMap<String, String>, ServerFnError> {
+    Ok(crate::config::config()
+        .agency_time_zones
+        .iter()
+        .filter(|(_, zone)| zone.parse::<chrono_tz::Tz>().is_ok())
+        .map(|(feed_id, zone)| (feed_id.clone(), zone.clone()))
+        .collect())
+}
+
+/// Call once from `App`, loads the saved zone and starts the clock in the browser.
+pub fn provide_time_preferences() {
+    let preferences = TimePreferences {
+        zone: RwSignal::new(TimeZoneChoice::Viewer),
+        now_ms: RwSignal::new(Utc::now().timestamp_millis()),
+        hydrated: RwSignal::new(false),
+        agency_time_zones: RwSignal::new(None),
+    };
+
+    provide_context(preferences);
+
+    // effects only run in the browser
+    Effect::new(move |_| {
+        if let Ok(Some(storage)) = window().local_storage()
+            && let Some(zone) = storage.get_item(STORAGE_KEY).ok().flatten().and_then(|x| TimeZoneChoice::parse(&x))
+        {
+            preferences.zone.set(zone);
+        }
+
+        preferences.now_ms.set(Utc::now().timestamp_millis());
+        preferences.hydrated.set(true);
+
+        set_interval(
+            move || preferences.now_ms.set(Utc::now().timestamp_millis()),
+            Duration::from_secs(1),
+        );
+    });
+
+    Effect::new(move |_| {
+        if preferences.zone.get() == TimeZoneChoice::Agency && preferences.agency_time_zones.get_untracked().is_none() {
+            spawn_local(async move {
+                match load_agency_time_zones().await {
+                    Ok(zones) => preferences.agency_time_zones.set(Some(zones)),
+                    Err(err) => leptos::logging::warn!("Could not load agency time zones, {}", err),
+                }
+            });
+        }
+    });
+}
+
+/// The absolute part of a `Time`. Agency time falls back to UTC without a known `agency_time_zone`.
+pub fn format_absolute(time: DateTime<Utc>, zone: TimeZoneChoice, agency_time_zone: Option<&str>, hydrated: bool) -> String {
+    let agency_time_zone = agency_time_zone.and_then(|x| x.parse::<chrono_tz::Tz>().ok());
+
+    match (zone, agency_time_zone) {
+        (TimeZoneChoice::Viewer, _) if hydrated => time.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S %:z").to_string(),
+        (TimeZoneChoice::Agency, Some(agency_time_zone)) => {
+            time.with_timezone(&agency_time_zone).format("%Y-%m-%d %H:%M:%S %Z").to_string()
+        }
+        _ => time.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+    }
+}
+
+fn format_relative(i18n: I18nContext<Locale>, seconds: i64) -> String {
+    match seconds {
+        ..10 => t_string!(i18n, time_just_now).to_string(),
+        10..60 => t_string!(i18n, time_seconds_ago, count = seconds).to_string(),
+        60..3600 => t_string!(i18n, time_minutes_ago, count = seconds / 60).to_string(),
+        3600..86400 => t_string!(i18n, time_hours_ago, count = seconds / 3600).to_string(),
+        _ => t_string!(i18n, time_days_ago, count = seconds / 86400).to_string(),
+    }
+}
+
+/// A millisecond unix timestamp, e.g. "2025-03-01 14:02:11 -08:00 (32 s ago)".
+/// `feed_id` is the feed the timestamp belongs to, whose agency zone is used for agency time.
+#[component]
+pub fn Time(time_ms: i64, #[prop(default = None, into)] feed_id: Option<String>) -> impl IntoView {
+    let i18n = use_i18n();
+    let preferences = expect_context::<TimePreferences>();
+
+    let time = match DateTime::from_timestamp_millis(time_ms) {
+        Some(time) => time,
+        None => return view! { <span>{move || t_string!(i18n, time_unknown)}</span> }.into_any(),
+    };
+
+    let absolute = move || {
+        let agency_time_zones = preferences.agency_time_zones.read();
+        let agency_time_zone = feed_id
+            .as_ref()
+            .and_then(|feed_id| agency_time_zones.as_ref()?.get(feed_id.trim()))
+            .map(|x| x.as_str());
+
+        format_absolute(time, preferences.zone.get(), agency_time_zone, preferences.hydrated.get())
+    };
+
+    let relative = move || {
+        preferences.hydrated.get().then(|| {
+            format!(" ({})", format_relative(i18n, (preferences.now_ms.get() - time_ms) / 1000))
+        })
+    };
+
+    view! {
+        <time class="font-mono" datetime=time.to_rfc3339() title=time.format("%Y-%m-%d %H:%M:%S UTC").to_string()>
+            {absolute}{relative}
+        </time>
+    }
+    .into_any()
+}
+
+/// Picks the zone every `Time` uses, saved in localStorage.
+#[component]
+pub fn TimeZoneSelect() -> impl IntoView {
+    let i18n = use_i18n();
+    let preferences = expect_context::<TimePreferences>();
+
+    view! {
+        <select
+            class="bg-gray dark:bg-darksky text-tulip font-semibold"
+            aria-label=move || t_string!(i18n, time_zone)
+            on:change=move |event| {
+                if let Some(zone) = TimeZoneChoice::parse(&event_target_value(&event)) {
+                    preferences.zone.set(zone);
+
+                    if let Ok(Some(storage)) = window().local_storage() {
+                        let _ = storage.set_item(STORAGE_KEY, zone.as_str());
+                    }
+                }
+            }
+        >
+            {
+                TimeZoneChoice::ALL.into_iter().map(|zone| view! {
+                    <option value=zone.as_str() selected=move || preferences.zone.get() == zone>
+                        {move || match zone {
+                            TimeZoneChoice::Viewer => t_string!(i18n, time_zone_viewer),
+                            TimeZoneChoice::Utc => t_string!(i18n, time_zone_utc),
+                            TimeZoneChoice::Agency => t_string!(i18n, time_zone_agency),
+                        }}
+                    </option>
+                }).collect_view()
+            }
+        </select>
+    }
+}
@@ -0,0 +1,540 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Tulip's own operator accounts, so the team does not have to share one Birch login.
+// Accounts live in the RON file set as users_file in tulip.ron, passwords are argon2id hashes.
+
+use crate::app::Nav;
+use crate::auth::Role;
+use crate::i18n::*;
+use crate::page_meta::PageMeta;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use leptos_i18n::I18nContext;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "ssr")]
+use std::collections::BTreeMap;
+
+pub const MINIMUM_PASSWORD_LENGTH: usize = 12;
+
+#[derive(Serialize, Clone, Deserialize, Debug)]
+pub struct UserAccount {
+    pub email: String,
+    /// PHC string, $argon2id$v=19$...
+    pub password_hash: String,
+    pub role: Role,
+    pub disabled: bool,
+}
+
+/// An account as shown on the admin page, without the hash.
+#[derive(Serialize, Clone, Deserialize, Debug)]
+pub struct UserSummary {
+    pub email: String,
+    pub role: Role,
+    pub disabled: bool,
+}
+
+#[cfg(feature = "ssr")]
+static USERS_FILE_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+#[cfg(feature = "ssr")]
+fn users_file() -> Result<&'static str, ServerFnError> {
+    match &crate::config::config().users_file {
+        Some(path) => Ok(path.as_str()),
+        None => Err(ServerFnError::new(
+            "Local accounts are not enabled, set users_file in tulip.ron",
+        )),
+    }
+}
+
+#[cfg(feature = "ssr")]
+fn read_users(path: &str) -> Result<BTreeMap<String, UserAccount>, ServerFnError> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => Ok(ron::from_str(&text)?),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[cfg(feature = "ssr")]
+fn write_users(path: &str, users: &BTreeMap<String, UserAccount>) -> Result<(), ServerFnError> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    //write then rename so a crash never leaves a half written store
+    //the renamed file keeps the temporary file's mode, so the hashes are only readable by Tulip's own user
+    let temporary_path = format!("{}.tmp", path);
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(&temporary_path)?;
+    file.write_all(ron::ser::to_string_pretty(users, ron::ser::PrettyConfig::default())?.as_bytes())?;
+    std::fs::rename(&temporary_path, path)?;
+    Ok(())
+}
+
+/// Applies `change` to the stored accounts and saves them, holding the lock throughout.
+#[cfg(feature = "ssr")]
+fn modify_users<T>(
+    change: impl FnOnce(&mut BTreeMap<String, UserAccount>) -> Result<T, ServerFnError>,
+) -> Result<T, ServerFnError> {
+    let path = users_file()?;
+    let _guard = USERS_FILE_LOCK.lock().unwrap();
+
+    let mut users = read_users(path)?;
+    let result = change(&mut users)?;
+    write_users(path, &users)?;
+
+    Ok(result)
+}
+
+/// Local accounts take over from Birch logins once the store has at least one account.
+/// Until then an Admin Birch login can use the Operators page to create the first ones.
+#[cfg(feature = "ssr")]
+pub fn local_accounts_enabled() -> Result<bool, ServerFnError> {
+    match &crate::config::config().users_file {
+        Some(path) => {
+            let _guard = USERS_FILE_LOCK.lock().unwrap();
+            Ok(!read_users(path)?.is_empty())
+        }
+        None => Ok(false),
+    }
+}
+
+#[cfg(feature = "ssr")]
+pub fn hash_password(password: &str) -> Result<String, ServerFnError> {
+    use argon2::Argon2;
+    use argon2::password_hash::{PasswordHasher, SaltString, rand_core::OsRng};
+
+    let salt = SaltString::generate(&mut OsRng);
+
+    // Argon2::default() is argon2id v19
+    match Argon2::default().hash_password(password.as_bytes(), &salt) {
+        Ok(hash) => Ok(hash.to_string()),
+        Err(err) => Err(ServerFnError::new(format!("Could not hash password, {}", err))),
+    }
+}
+
+#[cfg(feature = "ssr")]
+pub fn verify_password(password: &str, password_hash: &str) -> bool {
+    use argon2::Argon2;
+    use argon2::password_hash::{PasswordHash, PasswordVerifier};
+
+    match PasswordHash::new(password_hash) {
+        Ok(parsed_hash) => Argon2::default()
+            .verify_password(password.as_bytes(), &parsed_hash)
+            .is_ok(),
+        Err(err) => {
+            println!("Stored password hash is unreadable, {}", err);
+            false
+        }
+    }
+}
+
+/// The account for these credentials, if it exists, is enabled and the password matches.
+#[cfg(feature = "ssr")]
+pub fn check_login(email: &str, password: &str) -> Result<Option<UserAccount>, ServerFnError> {
+    let path = users_file()?;
+
+    let account = {
+        let _guard = USERS_FILE_LOCK.lock().unwrap();
+        read_users(path)?.remove(email)
+    };
+
+    match account {
+        Some(account) if !account.disabled && verify_password(password, &account.password_hash) => {
+            Ok(Some(account))
+        }
+        _ => Ok(None),
+    }
+}
+
+#[cfg(feature = "ssr")]
+fn check_new_password(password: &str) -> Result<(), ServerFnError> {
+    match password.chars().count() >= MINIMUM_PASSWORD_LENGTH {
+        true => Ok(()),
+        false => Err(ServerFnError::new(format!(
+            "Passwords must be at least {} characters long",
+            MINIMUM_PASSWORD_LENGTH
+        ))),
+    }
+}
+
+#[cfg(feature = "ssr")]
+async fn authenticate_admin(
+    master_email: &str,
+    master_password: &str,
+) -> Result<Option<crate::auth::Operator>, ServerFnError> {
+    match crate::auth::authenticate_verified(master_email, master_password).await? {
+        Some(operator) => {
+            crate::auth::require_role(&operator, Role::Admin)?;
+            Ok(Some(operator))
+        }
+        None => Ok(None),
+    }
+}
+
+#[server(endpoint = "list_users")]
+pub async fn list_users(
+    master_email: String,
+    master_password: String,
+) -> Result<Option<Vec<UserSummary>>, ServerFnError> {
+    if authenticate_admin(&master_email, &master_password).await?.is_none() {
+        return Ok(None);
+    }
+
+    let path = users_file()?;
+    let _guard = USERS_FILE_LOCK.lock().unwrap();
+
+    Ok(Some(
+        read_users(path)?
+            .into_values()
+            .map(|account| UserSummary {
+                email: account.email,
+                role: account.role,
+                disabled: account.disabled,
+            })
+            .collect(),
+    ))
+}
+
+#[cfg(feature = "ssr")]
+async fn add_user_checked(
+    master_email: String,
+    master_password: String,
+    new_email: String,
+    new_password: String,
+    new_role: Role,
+) -> Result<bool, ServerFnError> {
+    let operator = match authenticate_admin(&master_email, &master_password).await? {
+        Some(operator) => operator,
+        None => return Ok(false),
+    };
+
+    let new_email = new_email.trim().to_string();
+
+    if new_email.is_empty() {
+        return Err(ServerFnError::new("Email cannot be empty"));
+    }
+
+    check_new_password(&new_password)?;
+    let password_hash = hash_password(&new_password)?;
+
+    modify_users(|users| {
+        if users.contains_key(&new_email) {
+            return Err(ServerFnError::new(format!("{} already has an account", new_email)));
+        }
+
+        users.insert(
+            new_email.clone(),
+            UserAccount {
+                email: new_email.clone(),
+                password_hash,
+                role: new_role,
+                disabled: false,
+            },
+        );
+
+        Ok(())
+    })?;
+
+    println!("{} added operator {} as {}", operator.email, new_email, new_role.name());
+
+    Ok(true)
+}
+
+#[server(endpoint = "add_user")]
+pub async fn add_user(
+    master_email: String,
+    master_password: String,
+    new_email: String,
+    new_password: String,
+    new_role: Role,
+) -> Result<bool, ServerFnError> {
+    use crate::audit::{AuditAction, AuditEntry, AuditOutcome};
+
+    let mut entry = AuditEntry::new(&master_email, AuditAction::AddUser, None);
+    entry.after = Some(format!("{} ({})", new_email.trim(), new_role.name()));
+    let result = add_user_checked(master_email, master_password, new_email, new_password, new_role).await;
+    entry.outcome = AuditOutcome::from_bool(&result);
+    crate::audit::record(entry)?;
+
+    result
+}
+
+#[cfg(feature = "ssr")]
+async fn set_user_disabled_checked(
+    master_email: String,
+    master_password: String,
+    target_email: String,
+    disabled: bool,
+) -> Result<bool, ServerFnError> {
+    let operator = match authenticate_admin(&master_email, &master_password).await? {
+        Some(operator) => operator,
+        None => return Ok(false),
+    };
+
+    if disabled && operator.email == target_email {
+        return Err(ServerFnError::new("You cannot disable your own account"));
+    }
+
+    modify_users(|users| match users.get_mut(&target_email) {
+        Some(account) => {
+            account.disabled = disabled;
+            Ok(())
+        }
+        None => Err(ServerFnError::new(format!("{} has no account", target_email))),
+    })?;
+
+    println!("{} set disabled={} on operator {}", operator.email, disabled, target_email);
+
+    Ok(true)
+}
+
+#[server(endpoint = "set_user_disabled")]
+pub async fn set_user_disabled(
+    master_email: String,
+    master_password: String,
+    target_email: String,
+    disabled: bool,
+) -> Result<bool, ServerFnError> {
+    use crate::audit::{AuditAction, AuditEntry, AuditOutcome};
+
+    let mut entry = AuditEntry::new(&master_email, AuditAction::SetUserDisabled, None);
+    entry.after = Some(format!("{} disabled: {}", target_email, disabled));
+    let result = set_user_disabled_checked(master_email, master_password, target_email, disabled).await;
+    entry.outcome = AuditOutcome::from_bool(&result);
+    crate::audit::record(entry)?;
+
+    result
+}
+
+#[cfg(feature = "ssr")]
+async fn change_password_checked(
+    master_email: String,
+    master_password: String,
+    new_password: String,
+) -> Result<bool, ServerFnError> {
+    let operator = match crate::auth::authenticate_verified(&master_email, &master_password).await? {
+        Some(operator) => operator,
+        None => return Ok(false),
+    };
+
+    if !operator.local {
+        return Err(ServerFnError::new(
+            "This is a Birch login, its password can only be changed in Birch",
+        ));
+    }
+
+    check_new_password(&new_password)?;
+    let password_hash = hash_password(&new_password)?;
+
+    modify_users(|users| match users.get_mut(&operator.email) {
+        Some(account) => {
+            account.password_hash = password_hash;
+            Ok(())
+        }
+        None => Err(ServerFnError::new("Your account no longer exists")),
+    })?;
+
+    println!("{} changed their password", operator.email);
+
+    Ok(true)
+}
+
+#[server(endpoint = "change_password")]
+pub async fn change_password(
+    master_email: String,
+    master_password: String,
+    new_password: String,
+) -> Result<bool, ServerFnError> {
+    use crate::audit::{AuditAction, AuditEntry, AuditOutcome};
+
+    let mut entry = AuditEntry::new(&master_email, AuditAction::ChangePassword, None);
+    let result = change_password_checked(master_email, master_password, new_password).await;
+    entry.outcome = AuditOutcome::from_bool(&result);
+    crate::audit::record(entry)?;
+
+    result
+}
+
+/// The translated name of a role, `Role::name` is kept for logs and the audit trail.
+pub fn role_label(i18n: I18nContext<Locale>, role: Role) -> &'static str {
+    match role {
+        Role::Viewer => t_string!(i18n, role_viewer),
+        Role::KeyEditor => t_string!(i18n, role_key_editor),
+        Role::Admin => t_string!(i18n, role_admin),
+    }
+}
+
+#[component]
+pub fn Users() -> impl IntoView {
+    let i18n = use_i18n();
+
+    let (master_email, set_master_email) = signal(String::from(""));
+    let (master_password, set_master_password) = signal(String::from(""));
+
+    let users: RwSignal<Option<Vec<UserSummary>>> = RwSignal::new(None);
+    let (message, set_message) = signal(String::from(""));
+
+    let (new_email, set_new_email) = signal(String::from(""));
+    let (new_password, set_new_password) = signal(String::from(""));
+    let (new_role, set_new_role) = signal(Role::Viewer);
+
+    let (changed_password, set_changed_password) = signal(String::from(""));
+
+    let reload = move || {
+        let (master_email, master_password) = (master_email.get(), master_password.get());
+
+        spawn_local(async move {
+            match list_users(master_email, master_password).await {
+                Ok(Some(list)) => {
+                    users.set(Some(list));
+                    set_message.set(String::from(""));
+                }
+                Ok(None) => set_message.set(t_string!(i18n, not_authorised).to_string()),
+                Err(err) => set_message.set(err.to_string()),
+            }
+        });
+    };
+
+    let show_result = move |result: Result<bool, ServerFnError>, success: &str| match result {
+        Ok(true) => {
+            set_message.set(success.to_string());
+            reload();
+        }
+        Ok(false) => set_message.set(t_string!(i18n, not_authorised).to_string()),
+        Err(err) => set_message.set(err.to_string()),
+    };
+
+    view! {
+        <PageMeta title=move || t_string!(i18n, nav_operators) noindex=true/>
+        <Nav/>
+        <main class="p-8">
+            <h1 class="text-2xl font-bold text-tulip">{t!(i18n, nav_operators)}</h1>
+            <p>{t!(i18n, users_intro)}</p>
+
+            <form on:submit=move |event| {
+                event.prevent_default();
+                reload();
+            }>
+                <input
+                    type="email"
+                    placeholder=move || t_string!(i18n, email)
+                    prop:value=move || master_email.get()
+                    class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold mr-4"
+                    on:input=move |event| set_master_email.set(event_target_value(&event))
+                />
+                <input
+                    type="password"
+                    autocomplete="current-password"
+                    placeholder=move || t_string!(i18n, password)
+                    prop:value=move || master_password.get()
+                    class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold mr-4"
+                    on:input=move |event| set_master_password.set(event_target_value(&event))
+                />
+                <button type="submit" class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold">{t!(i18n, load)}</button>
+            </form>
+
+            <p class="font-semibold">{move || message.get()}</p>
+
+            <h2 class="text-xl font-semibold mt-4">{t!(i18n, users_change_password)}</h2>
+            <form on:submit=move |event| {
+                event.prevent_default();
+                let (master_email, master_password, changed_password) = (master_email.get(), master_password.get(), changed_password.get());
+                spawn_local(async move {
+                    show_result(change_password(master_email, master_password, changed_password).await, t_string!(i18n, users_password_changed));
+                });
+            }>
+                <input
+                    type="password"
+                    autocomplete="new-password"
+                    placeholder=move || t_string!(i18n, users_new_password)
+                    prop:value=move || changed_password.get()
+                    class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold mr-4"
+                    on:input=move |event| set_changed_password.set(event_target_value(&event))
+                />
+                <button type="submit" class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold">{t!(i18n, users_change_password_button)}</button>
+            </form>
+
+            {
+                move || users.get().map(|list| view! {
+                    <h2 class="text-xl font-semibold mt-4">{t!(i18n, users_accounts)}</h2>
+                    <table class="my-4">
+                        <tr class="text-left">
+                            <th class="pr-8">{t!(i18n, email)}</th>
+                            <th class="pr-8">{t!(i18n, users_role)}</th>
+                            <th class="pr-8">{t!(i18n, users_status)}</th>
+                            <th></th>
+                        </tr>
+                        {
+                            list.into_iter().map(|account| {
+                                let target_email = account.email.clone();
+                                let disabled = account.disabled;
+                                view! {
+                                    <tr>
+                                        <td class="pr-8">{account.email.clone()}</td>
+                                        <td class="pr-8">{move || role_label(i18n, account.role)}</td>
+                                        <td class="pr-8">{move || if disabled { t_string!(i18n, users_disabled) } else { t_string!(i18n, users_active) }}</td>
+                                        <td>
+                                            <button
+                                            class="underline text-blue-500 dark:text-blue-300"
+                                            on:click=move |_| {
+                                                let (master_email, master_password, target_email) = (master_email.get(), master_password.get(), target_email.clone());
+                                                spawn_local(async move {
+                                                    show_result(set_user_disabled(master_email, master_password, target_email, !disabled).await, t_string!(i18n, users_account_updated));
+                                                });
+                                            }
+                                            >{move || if disabled { t_string!(i18n, users_enable) } else { t_string!(i18n, users_disable) }}</button>
+                                        </td>
+                                    </tr>
+                                }
+                            }).collect_view()
+                        }
+                    </table>
+
+                    <h2 class="text-xl font-semibold">{t!(i18n, users_add_operator)}</h2>
+                    <form on:submit=move |event| {
+                        event.prevent_default();
+                        let (master_email, master_password, new_email, new_password, new_role) = (master_email.get(), master_password.get(), new_email.get(), new_password.get(), new_role.get());
+                        spawn_local(async move {
+                            show_result(add_user(master_email, master_password, new_email, new_password, new_role).await, t_string!(i18n, users_operator_added));
+                        });
+                    }>
+                        <input
+                            type="email"
+                            placeholder=move || t_string!(i18n, email)
+                            prop:value=move || new_email.get()
+                            class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold mr-4"
+                            on:input=move |event| set_new_email.set(event_target_value(&event))
+                        />
+                        <input
+                            type="password"
+                            autocomplete="new-password"
+                            placeholder=move || t_string!(i18n, users_initial_password)
+                            prop:value=move || new_password.get()
+                            class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold mr-4"
+                            on:input=move |event| set_new_password.set(event_target_value(&event))
+                        />
+                        <select
+                            class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold mr-4"
+                            on:change=move |event| set_new_role.set(match event_target_value(&event).as_str() {
+                                "Admin" => Role::Admin,
+                                "KeyEditor" => Role::KeyEditor,
+                                _ => Role::Viewer,
+                            })
+                        >
+                            <option value="Viewer">{move || role_label(i18n, Role::Viewer)}</option>
+                            <option value="KeyEditor">{move || role_label(i18n, Role::KeyEditor)}</option>
+                            <option value="Admin">{move || role_label(i18n, Role::Admin)}</option>
+                        </select>
+                        <button type="submit" class="bg-gray dark:bg-darksky rounded-md p-2 px-4 border-2 border-tulip my-4 text-lg font-bold">{t!(i18n, users_add)}</button>
+                    </form>
+                })
+            }
+        </main>
+    }
+}
@@ -3,7 +3,11 @@ module.exports = {
     content: {
         files: ["*.html", "./src/**/*.rs"],
     },
-    darkMode: "class",
+    // .dark and .light on <html> force a theme, otherwise follow the system
+    darkMode: ["variant", [
+        "&:is(.dark *)",
+        "@media (prefers-color-scheme: dark) { &:not(.light *) }",
+    ]],
     theme: {
         extend: {
             colors: {
@@ -0,0 +1,130 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Checks the audit log's CSV export and filters.
+// Run with `cargo test --features ssr`.
+
+#![cfg(feature = "ssr")]
+
+use tulip::audit::{AuditAction, AuditEntry, AuditFilter, AuditOutcome, entries_to_csv};
+
+/// 2024-03-01 12:00:00 UTC.
+const MARCH_FIRST_MS: i64 = 1_709_294_400_000;
+
+fn entry(actor: &str, feed_id: Option<&str>) -> AuditEntry {
+    AuditEntry {
+        time_ms: MARCH_FIRST_MS,
+        actor: actor.to_string(),
+        action: AuditAction::SubmitKeys,
+        feed_id: feed_id.map(|x| x.to_string()),
+        outcome: AuditOutcome::Success,
+        before: None,
+        after: None,
+    }
+}
+
+/// The CSV rows after the header.
+fn rows(entries: &[AuditEntry]) -> Vec<String> {
+    entries_to_csv(entries).lines().skip(1).map(|x| x.to_string()).collect()
+}
+
+#[test]
+fn csv_has_a_header_and_a_row_per_entry() {
+    let csv = entries_to_csv(&[entry("editor@example.com", Some("f-test~rt"))]);
+
+    assert_eq!(
+        csv,
+        "time,actor,action,feed_id,outcome,before,after\n\
+         2024-03-01 12:00:00 UTC,editor@example.com,Submit keys,f-test~rt,Success,,\n"
+    );
+}
+
+#[test]
+fn csv_quotes_commas_quotes_and_newlines() {
+    let mut entry = entry("editor@example.com", None);
+    entry.outcome = AuditOutcome::Failed(String::from("Birch said \"no\", twice"));
+    entry.after = Some(String::from("Some((\n    key_formats: [],\n))"));
+
+    let csv = entries_to_csv(&[entry]);
+    let row = csv.split_once('\n').unwrap().1;
+
+    assert_eq!(
+        row,
+        "2024-03-01 12:00:00 UTC,editor@example.com,Submit keys,,\"Failed, Birch said \"\"no\"\", twice\",,\"Some((\n    key_formats: [],\n))\"\n"
+    );
+}
+
+#[test]
+fn csv_keeps_formulas_as_text() {
+    for (actor, cell) in [
+        ("=HYPERLINK(\"http://evil.example\")", "\"'=HYPERLINK(\"\"http://evil.example\"\")\""),
+        ("+1", "'+1"),
+        ("-1", "'-1"),
+        ("@SUM(A1)", "'@SUM(A1)"),
+        ("\tcmd", "'\tcmd"),
+        ("\rcmd", "\"'\rcmd\""),
+    ] {
+        let row = rows(&[entry(actor, None)]).concat();
+        let fields = row.strip_prefix("2024-03-01 12:00:00 UTC,").unwrap();
+
+        assert!(fields.starts_with(&format!("{},", cell)), "{:?} became {:?}", actor, fields);
+    }
+
+    // only the first character matters
+    assert!(rows(&[entry("editor=1@example.com", None)])[0].contains(",editor=1@example.com,"));
+}
+
+#[test]
+fn empty_filter_matches_everything() {
+    assert!(AuditFilter::default().matches(&entry("editor@example.com", None)).unwrap());
+}
+
+#[test]
+fn filter_matches_part_of_the_actor() {
+    let filter = AuditFilter {
+        actor: String::from(" editor@ "),
+        ..Default::default()
+    };
+
+    assert!(filter.matches(&entry("editor@example.com", None)).unwrap());
+    assert!(!filter.matches(&entry("viewer@example.com", None)).unwrap());
+}
+
+#[test]
+fn filter_matches_the_whole_feed_id() {
+    let filter = AuditFilter {
+        feed_id: String::from("f-test~rt "),
+        ..Default::default()
+    };
+
+    assert!(filter.matches(&entry("editor@example.com", Some("f-test~rt"))).unwrap());
+    assert!(!filter.matches(&entry("editor@example.com", Some("f-test~rt~2"))).unwrap());
+    assert!(!filter.matches(&entry("editor@example.com", None)).unwrap());
+}
+
+#[test]
+fn filter_dates_are_inclusive() {
+    let on = |from_date: &str, to_date: &str| AuditFilter {
+        from_date: from_date.to_string(),
+        to_date: to_date.to_string(),
+        ..Default::default()
+    };
+    let entry = entry("editor@example.com", None);
+
+    assert!(on("2024-03-01", "2024-03-01").matches(&entry).unwrap());
+    assert!(on("2024-02-01", "").matches(&entry).unwrap());
+    assert!(on("", "2024-03-31").matches(&entry).unwrap());
+    assert!(!on("2024-03-02", "").matches(&entry).unwrap());
+    assert!(!on("", "2024-02-29").matches(&entry).unwrap());
+}
+
+#[test]
+fn filter_rejects_a_date_that_does_not_parse() {
+    let filter = AuditFilter {
+        from_date: String::from("1 March 2024"),
+        ..Default::default()
+    };
+
+    assert!(filter.matches(&entry("editor@example.com", None)).is_err());
+}
@@ -0,0 +1,32 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Secrets as written to the audit log and shown in the key history.
+
+use tulip::app::{KeyFormat, PasswordFormat, PasswordInfo, REDACTED_PASSWORD};
+
+fn fingerprint(secret: &str) -> String {
+    let format = PasswordFormat {
+        key_formats: vec![KeyFormat::Header(String::from("api_key"))],
+        passwords: vec![PasswordInfo {
+            password: vec![secret.to_string()],
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+
+    format.fingerprinted().passwords[0].password[0].clone()
+}
+
+#[test]
+fn long_secrets_keep_their_last_4_characters() {
+    assert_eq!(fingerprint("c3ab117ab77aa801f706e6bea12f5612"), "<redacted ...5612>");
+}
+
+#[test]
+fn short_secrets_are_hidden_entirely() {
+    assert_eq!(fingerprint("abcd"), REDACTED_PASSWORD);
+    assert_eq!(fingerprint("secret-key1"), REDACTED_PASSWORD);
+    assert_eq!(fingerprint(""), REDACTED_PASSWORD);
+}
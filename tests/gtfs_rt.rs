@@ -0,0 +1,114 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Timestamps read from probed GTFS-rt feeds, and how they are shown in agency time.
+
+use chrono::DateTime;
+use tulip::gtfs_rt::{FeedTimestamps, feed_timestamps};
+use tulip::time::{TimeZoneChoice, format_absolute};
+
+fn varint(mut value: u64) -> Vec<u8> {
+    let mut out = vec![];
+    while value >= 0x80 {
+        out.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+    out
+}
+
+fn varint_field(number: u64, value: u64) -> Vec<u8> {
+    [varint(number << 3), varint(value)].concat()
+}
+
+fn bytes_field(number: u64, bytes: &[u8]) -> Vec<u8> {
+    [varint((number << 3) | 2), varint(bytes.len() as u64), bytes.to_vec()].concat()
+}
+
+/// FeedHeader with gtfs_realtime_version and, unless 0, timestamp.
+fn header(timestamp: u64) -> Vec<u8> {
+    let mut header = bytes_field(1, b"2.0");
+    if timestamp > 0 {
+        header.extend(varint_field(3, timestamp));
+    }
+    bytes_field(1, &header)
+}
+
+/// FeedEntity with an id and a VehiclePosition, which has a trip and, unless 0, a timestamp.
+fn vehicle(id: &str, timestamp: u64) -> Vec<u8> {
+    let mut position = bytes_field(1, &bytes_field(1, b"trip-1"));
+    // a fixed32 position, skipped over
+    position.extend([0x15, 0, 0, 0x80, 0x3f]);
+    if timestamp > 0 {
+        position.extend(varint_field(5, timestamp));
+    }
+    bytes_field(2, &[bytes_field(1, id.as_bytes()), bytes_field(4, &position)].concat())
+}
+
+#[test]
+fn reads_the_header_and_vehicle_timestamps() {
+    let message = [
+        header(1_709_294_400),
+        vehicle("a", 1_709_294_390),
+        vehicle("b", 1_709_294_300),
+        vehicle("c", 0),
+        vehicle("d", 1_709_294_395),
+    ]
+    .concat();
+
+    assert_eq!(
+        feed_timestamps(&message),
+        Some(FeedTimestamps {
+            header_ms: Some(1_709_294_400_000),
+            vehicles: 3,
+            oldest_vehicle_ms: Some(1_709_294_300_000),
+            newest_vehicle_ms: Some(1_709_294_395_000),
+        })
+    );
+}
+
+#[test]
+fn a_feed_without_timestamps_still_decodes() {
+    assert_eq!(feed_timestamps(&header(0)), Some(FeedTimestamps::default()));
+}
+
+#[test]
+fn other_responses_are_not_feeds() {
+    assert_eq!(feed_timestamps(b""), None);
+    assert_eq!(feed_timestamps(b"{\"header\": {}}"), None);
+    assert_eq!(feed_timestamps(b"<html>Unauthorized</html>"), None);
+
+    // cut off part way through the header
+    let message = header(1_709_294_400);
+    assert_eq!(feed_timestamps(&message[..message.len() - 2]), None);
+}
+
+#[test]
+fn agency_time_uses_the_feed_zone() {
+    let time = DateTime::from_timestamp(1_709_294_400, 0).unwrap();
+
+    assert_eq!(
+        format_absolute(time, TimeZoneChoice::Agency, Some("America/Los_Angeles"), true),
+        "2024-03-01 04:00:00 PST"
+    );
+    assert_eq!(
+        format_absolute(time, TimeZoneChoice::Agency, Some("Europe/Berlin"), false),
+        "2024-03-01 13:00:00 CET"
+    );
+}
+
+#[test]
+fn agency_time_falls_back_to_utc() {
+    let time = DateTime::from_timestamp(1_709_294_400, 0).unwrap();
+
+    assert_eq!(format_absolute(time, TimeZoneChoice::Agency, None, true), "2024-03-01 12:00:00 UTC");
+    assert_eq!(
+        format_absolute(time, TimeZoneChoice::Agency, Some("Mars/Olympus_Mons"), true),
+        "2024-03-01 12:00:00 UTC"
+    );
+    assert_eq!(
+        format_absolute(time, TimeZoneChoice::Utc, Some("America/Los_Angeles"), true),
+        "2024-03-01 12:00:00 UTC"
+    );
+}
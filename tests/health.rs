@@ -0,0 +1,89 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Checks /healthz, /readyz and /version.
+// Run with `cargo test --features ssr`.
+
+#![cfg(feature = "ssr")]
+
+use actix_web::{HttpResponse, HttpServer, test, web};
+use std::sync::Once;
+
+static CONFIG: Once = Once::new();
+
+/// Points Birch at a port nothing listens on, before the config is first loaded.
+fn unreachable_birch_config() {
+    CONFIG.call_once(|| {
+        let path = std::env::temp_dir().join(format!("tulip_health_test_{}.ron", std::process::id()));
+        std::fs::write(&path, r#"(birch_url: "http://127.0.0.1:9")"#).unwrap();
+
+        // SAFETY: runs once, before any test reads the environment
+        unsafe { std::env::set_var("TULIP_CONFIG", &path) };
+    });
+}
+
+macro_rules! test_app {
+    () => {{
+        unreachable_birch_config();
+
+        test::init_service(
+            actix_web::App::new()
+                .route("/healthz", web::get().to(tulip::health::healthz))
+                .route("/readyz", web::get().to(tulip::health::readyz))
+                .route("/version", web::get().to(tulip::health::version)),
+        )
+        .await
+    }};
+}
+
+#[actix_web::test]
+async fn healthz_is_ok() {
+    let app = test_app!();
+
+    let response: serde_json::Value =
+        test::call_and_read_body_json(&app, test::TestRequest::get().uri("/healthz").to_request()).await;
+
+    assert_eq!(response["status"], "ok");
+}
+
+#[actix_web::test]
+async fn readyz_fails_without_birch() {
+    let app = test_app!();
+
+    let response = test::call_service(&app, test::TestRequest::get().uri("/readyz").to_request()).await;
+    assert_eq!(response.status(), 503);
+
+    let body: serde_json::Value = test::read_body_json(response).await;
+    assert_eq!(body["ready"], false);
+    assert_eq!(body["birch_url"], "http://127.0.0.1:9");
+    assert!(body["config"].as_str().unwrap().ends_with(".ron"));
+    assert!(body["birch_error"].is_string());
+}
+
+#[actix_web::test]
+async fn check_birch_accepts_any_answer() {
+    let server = HttpServer::new(|| {
+        actix_web::App::new().default_service(web::to(|| async { HttpResponse::NotFound().finish() }))
+    })
+    .bind("127.0.0.1:0")
+    .unwrap();
+
+    let address = server.addrs()[0];
+    actix_web::rt::spawn(server.run());
+
+    assert!(tulip::health::check_birch(&format!("http://{}", address)).await.is_ok());
+    assert!(tulip::health::check_birch("http://127.0.0.1:9").await.is_err());
+}
+
+#[actix_web::test]
+async fn version_reports_build() {
+    let app = test_app!();
+
+    let response: serde_json::Value =
+        test::call_and_read_body_json(&app, test::TestRequest::get().uri("/version").to_request()).await;
+
+    assert_eq!(response["version"], env!("CARGO_PKG_VERSION"));
+    assert!(!response["git_hash"].as_str().unwrap().is_empty());
+    assert!(response["build_time"].as_str().unwrap().contains('T'));
+}
@@ -0,0 +1,113 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Fails when a locale is missing a key, has one English lacks, or when the code uses a key
+// that no locale defines. leptos_i18n only falls back to English for missing keys.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+fn load_locales() -> BTreeMap<String, BTreeMap<String, String>> {
+    std::fs::read_dir("locales")
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|x| x == "json"))
+        .map(|path| {
+            let name = path.file_stem().unwrap().to_string_lossy().to_string();
+            let text = std::fs::read_to_string(&path).unwrap();
+            let keys: BTreeMap<String, String> = serde_json::from_str(&text)
+                .unwrap_or_else(|err| panic!("{} is not a flat map of strings: {}", path.display(), err));
+            (name, keys)
+        })
+        .collect()
+}
+
+/// The names inside {{ }}, which have to be the same in every translation of a key.
+fn variables(text: &str) -> BTreeSet<String> {
+    text.split("{{")
+        .skip(1)
+        .filter_map(|x| x.split("}}").next())
+        .map(|x| x.trim().to_string())
+        .collect()
+}
+
+#[test]
+fn every_locale_has_every_key() {
+    let locales = load_locales();
+    let english = locales.get("en").expect("locales/en.json is missing");
+
+    assert!(locales.len() >= 2, "expected English and at least one other locale");
+
+    for (name, keys) in &locales {
+        let missing = english.keys().filter(|key| !keys.contains_key(*key)).collect::<Vec<_>>();
+        let extra = keys.keys().filter(|key| !english.contains_key(*key)).collect::<Vec<_>>();
+
+        assert!(missing.is_empty(), "{} is missing {:?}", name, missing);
+        assert!(extra.is_empty(), "{} has keys English does not: {:?}", name, extra);
+
+        for (key, text) in keys {
+            assert!(!text.trim().is_empty(), "{}.{} is empty", name, key);
+            assert_eq!(variables(text), variables(&english[key]), "{}.{} has different variables", name, key);
+        }
+    }
+}
+
+#[test]
+fn every_locale_is_listed_in_cargo_toml() {
+    let locales = load_locales();
+    let cargo_toml = std::fs::read_to_string("Cargo.toml").unwrap();
+
+    let listed = cargo_toml
+        .split("[package.metadata.leptos-i18n]")
+        .nth(1)
+        .and_then(|x| x.lines().find(|line| line.trim_start().starts_with("locales")))
+        .expect("no locales in [package.metadata.leptos-i18n]");
+
+    for name in locales.keys() {
+        assert!(listed.contains(&format!("\"{}\"", name)), "{} is not listed in Cargo.toml", name);
+    }
+}
+
+/// Every .rs file under `dir`, including src/bin.
+fn rust_files(dir: &std::path::Path) -> Vec<std::path::PathBuf> {
+    let mut files = vec![];
+
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+
+        if path.is_dir() {
+            files.extend(rust_files(&path));
+        } else if path.extension().is_some_and(|x| x == "rs") {
+            files.push(path);
+        }
+    }
+
+    files
+}
+
+#[test]
+fn every_key_used_in_the_code_exists() {
+    let locales = load_locales();
+    let english = &locales["en"];
+
+    for path in rust_files(std::path::Path::new("src")) {
+        let source = std::fs::read_to_string(&path).unwrap();
+
+        for macro_name in ["t!(", "t_string!(", "td_string!("] {
+            for (index, _) in source.match_indices(macro_name) {
+                // skip assert!( and the like
+                if source[..index].chars().last().is_some_and(|x| x.is_alphanumeric() || x == '_') {
+                    continue;
+                }
+
+                let key = source[index + macro_name.len()..]
+                    .split([',', ')'])
+                    .nth(1)
+                    .map(|x| x.trim())
+                    .unwrap_or_default();
+
+                assert!(english.contains_key(key), "{} uses {} which en.json lacks", path.display(), key);
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Why a feed is stale, from Alpenrose's fetch attempts and the last key change.
+
+use leptos::prelude::Owner;
+use leptos::task::Executor;
+use tulip::i18n::Locale;
+use tulip::ingestion::{FeedIngestionStatus, FetchAttempt, STALE_AFTER_MS, StaleReason, explain, is_stale};
+
+const NOW_MS: i64 = 1_750_000_000_000;
+
+fn attempt(seconds_ago: i64, http_status: Option<u16>) -> FetchAttempt {
+    FetchAttempt {
+        time_ms: NOW_MS - seconds_ago * 1000,
+        feed_type: String::from("vehicle"),
+        http_status,
+        bytes: 0,
+        parse_error: None,
+        error: None,
+        password_index: Some(0),
+    }
+}
+
+fn status(attempts: Vec<FetchAttempt>) -> FeedIngestionStatus {
+    FeedIngestionStatus {
+        last_success_ms: attempts.iter().find(|x| x.succeeded()).map(|x| x.time_ms),
+        last_failure_ms: attempts.iter().find(|x| !x.succeeded()).map(|x| x.time_ms),
+        keys_loaded_ms: Some(NOW_MS - 3_600_000),
+        recent_attempts: attempts,
+    }
+}
+
+#[test]
+fn healthy_feeds_are_not_stale() {
+    let healthy = status(vec![attempt(2, Some(200)), attempt(12, Some(200))]);
+
+    assert!(!is_stale(&healthy, NOW_MS));
+    assert!(explain(&healthy, None, NOW_MS).is_empty());
+}
+
+#[test]
+fn a_failed_latest_attempt_is_stale() {
+    let rejected = status(vec![attempt(2, Some(401)), attempt(12, Some(200))]);
+
+    assert!(is_stale(&rejected, NOW_MS));
+    assert_eq!(
+        explain(&rejected, None, NOW_MS),
+        vec![StaleReason::KeyRejected { status: 401, password_index: Some(0) }]
+    );
+
+    let rate_limited = status(vec![attempt(2, Some(429)), attempt(12, Some(200))]);
+    assert_eq!(
+        explain(&rate_limited, None, NOW_MS),
+        vec![StaleReason::RateLimited { password_index: Some(0) }]
+    );
+
+    let down = status(vec![FetchAttempt {
+        error: Some(String::from("connection refused")),
+        ..attempt(2, None)
+    }]);
+    assert_eq!(
+        explain(&down, None, NOW_MS),
+        vec![
+            StaleReason::AgencyError { status: None, error: Some(String::from("connection refused")) },
+            StaleReason::NeverSucceeded,
+        ]
+    );
+}
+
+#[test]
+fn unparseable_data_is_stale() {
+    let garbled = status(vec![FetchAttempt {
+        parse_error: Some(String::from("invalid wire type")),
+        ..attempt(2, Some(200))
+    }]);
+
+    assert!(is_stale(&garbled, NOW_MS));
+    assert_eq!(
+        explain(&garbled, None, NOW_MS)[0],
+        StaleReason::ParseFailed { error: String::from("invalid wire type") }
+    );
+}
+
+#[test]
+fn old_successes_are_stale() {
+    let old = status(vec![attempt(STALE_AFTER_MS / 1000 + 60, Some(200))]);
+
+    assert!(is_stale(&old, NOW_MS));
+    assert_eq!(
+        explain(&old, None, NOW_MS),
+        vec![StaleReason::NotFetching { last_attempt_ms: Some(old.recent_attempts[0].time_ms) }]
+    );
+
+    let never = status(vec![]);
+    assert_eq!(
+        explain(&never, None, NOW_MS),
+        vec![StaleReason::NotFetching { last_attempt_ms: None }, StaleReason::NeverSucceeded]
+    );
+}
+
+#[test]
+fn key_changes_alpenrose_has_not_loaded_are_explained() {
+    let rejected = status(vec![attempt(2, Some(401)), attempt(12, Some(200))]);
+    let changed_ms = NOW_MS - 60_000;
+
+    let reasons = explain(&rejected, Some(changed_ms), NOW_MS);
+
+    assert_eq!(
+        reasons[0],
+        StaleReason::KeysNotReloaded { changed_ms, loaded_ms: rejected.keys_loaded_ms }
+    );
+    let _ = Executor::init_futures_executor();
+    Owner::new().with(|| {
+        let i18n = leptos_i18n::context::init_i18n_context::<Locale>();
+        assert!(reasons[0].describe(i18n).contains("keeps using the old keys"));
+    });
+
+    // reloaded since the change
+    let reloaded = FeedIngestionStatus {
+        keys_loaded_ms: Some(NOW_MS - 30_000),
+        ..rejected
+    };
+    assert!(!explain(&reloaded, Some(changed_ms), NOW_MS).iter().any(|x| matches!(x, StaleReason::KeysNotReloaded { .. })));
+}
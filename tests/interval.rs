@@ -0,0 +1,72 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// The fetch interval parsing shared by the form and submit_data.
+
+use tulip::interval::{FetchInterval, IntervalError, MAX_FETCH_INTERVAL_MS, MIN_FETCH_INTERVAL_MS, format_ms};
+
+fn every(ms: u32) -> Result<FetchInterval, IntervalError> {
+    Ok(FetchInterval::Every { ms })
+}
+
+#[test]
+fn parses_human_durations() {
+    assert_eq!(FetchInterval::parse("30s"), every(30_000));
+    assert_eq!(FetchInterval::parse("1500ms"), every(1_500));
+    assert_eq!(FetchInterval::parse("1.5s"), every(1_500));
+    assert_eq!(FetchInterval::parse("2m"), every(120_000));
+    assert_eq!(FetchInterval::parse(" 10 s "), every(10_000));
+    assert_eq!(FetchInterval::parse("5000"), every(5_000));
+    assert_eq!(FetchInterval::parse("default"), Ok(FetchInterval::Default));
+    assert_eq!(FetchInterval::parse("Default"), Ok(FetchInterval::Default));
+}
+
+#[test]
+fn parses_the_old_ron_input() {
+    assert_eq!(FetchInterval::parse("Some(1000)"), every(1_000));
+    assert_eq!(FetchInterval::parse("None"), Ok(FetchInterval::Default));
+}
+
+#[test]
+fn rejects_what_it_cannot_read() {
+    for text in ["", "soon", "1h", "-5s", "s", "Some()", "NaN", "inf"] {
+        assert_eq!(FetchInterval::parse(text), Err(IntervalError::Unrecognised), "{:?}", text);
+    }
+}
+
+#[test]
+fn enforces_the_bounds() {
+    assert_eq!(FetchInterval::parse("999ms"), Err(IntervalError::TooShort));
+    assert_eq!(FetchInterval::parse("0"), Err(IntervalError::TooShort));
+    assert_eq!(FetchInterval::parse(&format_ms(MIN_FETCH_INTERVAL_MS)), every(MIN_FETCH_INTERVAL_MS));
+    assert_eq!(FetchInterval::parse(&format_ms(MAX_FETCH_INTERVAL_MS)), every(MAX_FETCH_INTERVAL_MS));
+    assert_eq!(FetchInterval::parse("11m"), Err(IntervalError::TooLong));
+    assert_eq!(FetchInterval::parse("99999999999999"), Err(IntervalError::TooLong));
+}
+
+#[test]
+fn displays_what_it_parses() {
+    for text in ["default", "1500ms", "30s", "2m", "90s"] {
+        assert_eq!(FetchInterval::parse(text).unwrap().to_string(), text);
+    }
+
+    assert_eq!(FetchInterval::Every { ms: 60_000 }.to_string(), "1m");
+}
+
+#[test]
+fn converts_to_and_from_birch() {
+    assert_eq!(FetchInterval::from_birch(None), FetchInterval::Default);
+    assert_eq!(FetchInterval::from_birch(Some(5000)), FetchInterval::Every { ms: 5000 });
+    assert_eq!(FetchInterval::from_birch(Some(0)), FetchInterval::Default);
+    assert_eq!(FetchInterval::from_birch(Some(-1)), FetchInterval::Default);
+
+    assert_eq!(FetchInterval::Default.to_birch(), None);
+    assert_eq!(FetchInterval::Every { ms: 5000 }.to_birch(), Some(5000));
+}
+
+#[test]
+fn effective_interval_falls_back_to_alpenrose() {
+    assert_eq!(FetchInterval::Default.effective_ms(10_000), 10_000);
+    assert_eq!(FetchInterval::Every { ms: 2_000 }.effective_ms(10_000), 2_000);
+}
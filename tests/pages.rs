@@ -0,0 +1,94 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Renders pages on the server, without a browser or Birch.
+// Run with `cargo test --features ssr`.
+
+#![cfg(feature = "ssr")]
+
+use actix_files::Files;
+use actix_web::http::StatusCode;
+use actix_web::test;
+use leptos::prelude::get_configuration;
+use leptos_actix::{LeptosRoutes, generate_route_list, render_app_to_stream};
+use leptos_router::Method;
+use tulip::app::{App, shell};
+
+macro_rules! test_app {
+    () => {{
+        let leptos_options = get_configuration(Some("Cargo.toml")).unwrap().leptos_options;
+        let routes = generate_route_list(App);
+
+        test::init_service(
+            actix_web::App::new()
+                .leptos_routes(routes, {
+                    let leptos_options = leptos_options.clone();
+                    move || shell(leptos_options.clone())
+                })
+                // as in main.rs, anything that is not a file renders the app
+                .service(Files::new("/", leptos_options.site_root.to_string()).default_handler(render_app_to_stream(
+                    {
+                        let leptos_options = leptos_options.clone();
+                        move || shell(leptos_options.clone())
+                    },
+                    Method::Get,
+                ))),
+        )
+        .await
+    }};
+}
+
+async fn get(uri: &str) -> (StatusCode, String) {
+    let app = test_app!();
+
+    let response = test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+    let status = response.status();
+    let body = String::from_utf8(test::read_body(response).await.to_vec()).unwrap();
+
+    (status, body)
+}
+
+#[actix_web::test]
+async fn help_renders() {
+    let (status, body) = get("/help").await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("<title>Help · Tulip</title>"));
+    assert!(body.contains("Instructions"));
+    assert!(body.contains("pub struct PasswordFormat"));
+    // the WMATA and Bay Area examples
+    assert!(body.contains("api_key"));
+}
+
+#[actix_web::test]
+async fn unknown_pages_are_404() {
+    let (status, body) = get("/this-page-does-not-exist").await;
+
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(body.contains("404, this page doesn&#x27;t exist") || body.contains("404, this page doesn't exist"));
+    assert!(body.contains(r#"content="noindex, nofollow""#));
+}
+
+#[actix_web::test]
+async fn realtime_keys_starts_unauthorised() {
+    let (status, body) = get("/realtimekeys").await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("Please confirm your Tulip login credentials"));
+    assert!(body.contains("Not authorised"));
+    assert!(!body.contains("Authorised as"));
+    // nothing from Birch is rendered before logging in
+    assert!(!body.contains("Protobuf"));
+    assert!(!body.contains("<textarea"));
+}
+
+#[actix_web::test]
+async fn feed_pages_need_the_service_login() {
+    // there is no tulip.ron here, so no birch_email to read Birch's status with
+    let (status, body) = get("/feed/f-test~rt").await;
+
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert!(body.contains("Feed pages need birch_email and birch_password"));
+    assert!(body.contains(r#"content="noindex, nofollow""#));
+}
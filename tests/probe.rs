@@ -0,0 +1,80 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Which URLs a key is probed against, and which addresses Tulip refuses to fetch.
+
+use std::net::IpAddr;
+use tulip::app::PasswordFormat;
+use tulip::effective_config::AlpenroseFeedConfig;
+use tulip::rotation::{is_public_address, probe_urls};
+
+fn alpenrose() -> AlpenroseFeedConfig {
+    AlpenroseFeedConfig {
+        schedule_url: Some(String::from("https://example.com/gtfs.zip")),
+        realtime_vehicle_positions: Some(String::from("https://example.com/vehicles")),
+        realtime_trip_updates: Some(String::from("https://example.com/trips")),
+        realtime_alerts: None,
+        fetch_interval_ms: 10_000,
+        key_formats: vec![],
+        password_count: 1,
+    }
+}
+
+fn public(ip: &str) -> bool {
+    is_public_address(ip.parse::<IpAddr>().unwrap())
+}
+
+#[test]
+fn probes_alpenrose_urls_when_there_are_no_overrides() {
+    assert_eq!(
+        probe_urls(&PasswordFormat::default(), Some(&alpenrose())),
+        vec![String::from("https://example.com/vehicles"), String::from("https://example.com/trips")]
+    );
+}
+
+#[test]
+fn overrides_replace_alpenrose_urls() {
+    let format = PasswordFormat {
+        override_realtime_trip_updates: Some(String::from("https://agency.example.org/trips")),
+        override_alerts: Some(String::from("https://agency.example.org/alerts")),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        probe_urls(&format, Some(&alpenrose())),
+        vec![
+            String::from("https://example.com/vehicles"),
+            String::from("https://agency.example.org/trips"),
+            String::from("https://agency.example.org/alerts"),
+        ]
+    );
+    assert_eq!(probe_urls(&format, None).len(), 2);
+}
+
+#[test]
+fn refuses_internal_addresses() {
+    for ip in [
+        "127.0.0.1",
+        "10.1.2.3",
+        "172.16.0.1",
+        "192.168.1.1",
+        "169.254.169.254",
+        "100.64.0.1",
+        "0.0.0.0",
+        "::1",
+        "::",
+        "fd00::1",
+        "fe80::1",
+        "::ffff:127.0.0.1",
+    ] {
+        assert!(!public(ip), "{} should be refused", ip);
+    }
+}
+
+#[test]
+fn allows_public_addresses() {
+    for ip in ["93.184.215.14", "8.8.8.8", "2606:4700::1111", "::ffff:8.8.8.8"] {
+        assert!(public(ip), "{} should be allowed", ip);
+    }
+}
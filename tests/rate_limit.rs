@@ -0,0 +1,145 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Checks the per-IP request window and the failed login lockouts.
+// Run with `cargo test --features ssr`.
+
+#![cfg(feature = "ssr")]
+
+use actix_web::test::{TestRequest, call_service, init_service};
+use actix_web::{HttpResponse, middleware, web};
+use std::sync::{Mutex, Once};
+use tulip::rate_limit::{MAX_TRACKED, check_account, limit_api_requests, record_failed_login, record_successful_login};
+
+static CONFIG: Once = Once::new();
+
+/// The failed login map is shared, so tests that fill or prune it run one at a time.
+static FAILED_LOGINS: Mutex<()> = Mutex::new(());
+
+/// Tight limits so a few requests reach them, before the config is first loaded.
+fn strict_config() {
+    CONFIG.call_once(|| {
+        let path = std::env::temp_dir().join(format!("tulip_rate_limit_test_{}.ron", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"(rate_limit: (
+                requests_per_minute_per_ip: 3,
+                free_failed_logins: 2,
+                lockout_base_seconds: 10,
+                lockout_max_seconds: 60,
+            ))"#,
+        )
+        .unwrap();
+
+        // SAFETY: runs once, before any test reads the environment
+        unsafe { std::env::set_var("TULIP_CONFIG", &path) };
+    });
+}
+
+/// Seconds left in the account's lockout, read back from the error.
+fn locked_for(email: &str) -> Option<u64> {
+    let err = check_account(email).err()?.to_string();
+    let seconds = err.split("try again in ").nth(1)?.split(' ').next()?;
+    Some(seconds.parse().unwrap())
+}
+
+#[actix_web::test]
+async fn each_ip_gets_its_own_window() {
+    strict_config();
+
+    let app = init_service(
+        actix_web::App::new()
+            .wrap(middleware::from_fn(limit_api_requests))
+            .route("/api/load_realtime_keys", web::post().to(HttpResponse::Ok))
+            .route("/", web::get().to(HttpResponse::Ok)),
+    )
+    .await;
+
+    let request = |ip: &str| {
+        TestRequest::post()
+            .uri("/api/load_realtime_keys")
+            .peer_addr(format!("{}:443", ip).parse().unwrap())
+            .to_request()
+    };
+
+    for _ in 0..3 {
+        assert_eq!(call_service(&app, request("192.0.2.1")).await.status(), 200);
+    }
+
+    let response = call_service(&app, request("192.0.2.1")).await;
+    assert_eq!(response.status(), 429);
+
+    let retry_after = response.headers().get("Retry-After").unwrap().to_str().unwrap().parse::<u64>().unwrap();
+    assert!((1..=60).contains(&retry_after), "{}", retry_after);
+
+    // another IP is unaffected, as are pages outside /api/
+    assert_eq!(call_service(&app, request("192.0.2.2")).await.status(), 200);
+
+    let page = TestRequest::get().uri("/").peer_addr("192.0.2.1:443".parse().unwrap()).to_request();
+    assert_eq!(call_service(&app, page).await.status(), 200);
+}
+
+#[test]
+fn lockouts_double_up_to_the_maximum() {
+    strict_config();
+    let _guard = FAILED_LOGINS.lock().unwrap();
+
+    let email = "backoff@example.com";
+
+    record_failed_login(email);
+    record_failed_login(email);
+    assert_eq!(locked_for(email), None);
+
+    // the remaining seconds round down, so a lockout of 10 reads as 9 or 10
+    record_failed_login(email);
+    assert!(matches!(locked_for(email), Some(9..=10)));
+
+    record_failed_login(email);
+    assert!(matches!(locked_for(email), Some(19..=20)));
+
+    record_failed_login(email);
+    assert!(matches!(locked_for(email), Some(39..=40)));
+
+    record_failed_login(email);
+    assert!(matches!(locked_for(email), Some(59..=60)));
+
+    record_failed_login(email);
+    assert!(matches!(locked_for(email), Some(59..=60)));
+
+    // accounts are matched regardless of case and surrounding spaces
+    assert!(locked_for(" Backoff@Example.com ").is_some());
+
+    record_successful_login(email);
+    assert_eq!(locked_for(email), None);
+}
+
+#[test]
+fn pruning_keeps_the_most_recent_failures() {
+    strict_config();
+    let _guard = FAILED_LOGINS.lock().unwrap();
+
+    let oldest = "oldest@example.com";
+    for _ in 0..3 {
+        record_failed_login(oldest);
+    }
+    assert!(locked_for(oldest).is_some());
+
+    for index in 0..MAX_TRACKED {
+        record_failed_login(&format!("flood-{}@example.com", index));
+    }
+
+    let newest = "newest@example.com";
+    for _ in 0..3 {
+        record_failed_login(newest);
+    }
+
+    // the map filled up while nothing was old enough to forget, so the oldest half went
+    assert_eq!(locked_for(oldest), None);
+    assert!(locked_for(newest).is_some());
+
+    for index in 0..MAX_TRACKED {
+        record_successful_login(&format!("flood-{}@example.com", index));
+    }
+    record_successful_login(newest);
+}
@@ -0,0 +1,94 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Checks the generated robots.txt.
+// Run with `cargo test --features ssr`.
+
+#![cfg(feature = "ssr")]
+
+use tulip::robots::{ADMIN_PATHS, RobotsConfig, RobotsRule, robots_txt};
+
+fn group<'a>(robots: &'a str, user_agent: &str) -> &'a str {
+    robots
+        .split("\n\n")
+        .find(|group| group.starts_with(&format!("User-agent: {}\n", user_agent)))
+        .unwrap_or_else(|| panic!("no group for {}", user_agent))
+}
+
+#[test]
+fn default_bans_ai_crawlers_and_hides_admin_paths() {
+    let robots = robots_txt(&RobotsConfig::default());
+
+    assert_eq!(group(&robots, "GPTBot"), "User-agent: GPTBot\nDisallow: /");
+    assert_eq!(group(&robots, "ClaudeBot"), "User-agent: ClaudeBot\nDisallow: /");
+
+    let everyone = group(&robots, "*");
+    for path in ADMIN_PATHS {
+        assert!(everyone.lines().any(|line| line == format!("Disallow: {}", path)));
+    }
+    assert!(!everyone.contains("Disallow: /\n"));
+
+    assert!(!robots.contains("Sitemap:"));
+    assert!(robots.ends_with('\n'));
+}
+
+#[test]
+fn rules_and_sitemap_come_from_config() {
+    let config = RobotsConfig {
+        banned_bots: vec![String::from("BadBot")],
+        rules: vec![
+            RobotsRule {
+                user_agent: String::from("Googlebot"),
+                allow: vec![String::from("/help")],
+                disallow: vec![String::from("/private")],
+            },
+            RobotsRule {
+                user_agent: String::from("*"),
+                allow: vec![],
+                disallow: vec![String::from("/realtimekeys")],
+            },
+        ],
+        sitemap_url: Some(String::from("https://tulip.example/sitemap.xml")),
+        ..RobotsConfig::default()
+    };
+
+    let robots = robots_txt(&config);
+
+    assert_eq!(group(&robots, "BadBot"), "User-agent: BadBot\nDisallow: /");
+    assert!(!robots.contains("GPTBot"));
+
+    let googlebot = group(&robots, "Googlebot");
+    assert!(googlebot.contains("Allow: /help"));
+    assert!(googlebot.contains("Disallow: /private"));
+    // the admin paths are repeated, Googlebot ignores the "*" group
+    assert!(googlebot.contains("Disallow: /users"));
+
+    let everyone = group(&robots, "*");
+    assert_eq!(everyone.matches("Disallow: /realtimekeys").count(), 1);
+    assert_eq!(robots.matches("User-agent: *").count(), 1);
+
+    assert!(robots.ends_with("\n\nSitemap: https://tulip.example/sitemap.xml\n"));
+}
+
+#[test]
+fn admin_paths_can_be_left_crawlable() {
+    let config = RobotsConfig {
+        banned_bots: vec![],
+        disallow_admin_paths: false,
+        ..RobotsConfig::default()
+    };
+
+    assert_eq!(robots_txt(&config), "\n");
+
+    let config = RobotsConfig {
+        rules: vec![RobotsRule {
+            user_agent: String::from("*"),
+            allow: vec![],
+            disallow: vec![],
+        }],
+        ..config
+    };
+
+    assert_eq!(robots_txt(&config), "User-agent: *\nDisallow:\n");
+}
@@ -0,0 +1,82 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Choosing how Alpenrose rotates through a feed's passwords.
+
+use tulip::app::{KeyFormat, PasswordFormat, PasswordInfo, RotationStrategy};
+use tulip::rotation::{RotationError, validate_rotation};
+
+fn format(rotation: RotationStrategy, weights: &[Option<u32>]) -> PasswordFormat {
+    PasswordFormat {
+        key_formats: vec![KeyFormat::Header(String::from("api_key"))],
+        passwords: weights
+            .iter()
+            .map(|weight| PasswordInfo {
+                password: vec![String::from("secret")],
+                weight: *weight,
+                ..Default::default()
+            })
+            .collect(),
+        rotation,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn rotation_defaults_to_round_robin() {
+    let format: PasswordFormat = ron::from_str(
+        r#"(key_formats: [Header("api_key")], passwords: [(password: ["secret"], creator_email: "editor@example.com")], override_schedule_url: None, override_realtime_vehicle_positions: None, override_realtime_trip_updates: None, override_alerts: None)"#,
+    )
+    .unwrap();
+
+    assert_eq!(format.rotation, RotationStrategy::RoundRobin);
+    assert_eq!(format.passwords[0].weight, None);
+}
+
+#[test]
+fn round_robin_is_left_out_when_serialised() {
+    let text = ron::to_string(&format(RotationStrategy::RoundRobin, &[None])).unwrap();
+    assert!(!text.contains("rotation"), "{}", text);
+
+    let text = ron::to_string(&format(RotationStrategy::Failover, &[None])).unwrap();
+    assert!(text.contains("rotation:Failover"), "{}", text);
+}
+
+#[test]
+fn weighted_round_trips() {
+    let weighted = format(RotationStrategy::Weighted, &[Some(3), Some(1)]);
+    let parsed: PasswordFormat = ron::from_str(&ron::to_string(&weighted).unwrap()).unwrap();
+
+    assert_eq!(parsed, weighted);
+}
+
+#[test]
+fn strategies_without_weights_are_valid() {
+    for strategy in [RotationStrategy::RoundRobin, RotationStrategy::Failover, RotationStrategy::Random] {
+        assert_eq!(validate_rotation(&format(strategy, &[None, None])), Ok(()));
+    }
+}
+
+#[test]
+fn weights_need_weighted_rotation() {
+    assert_eq!(
+        validate_rotation(&format(RotationStrategy::Failover, &[None, Some(2)])),
+        Err(RotationError::UnusedWeight { index: 1, strategy: RotationStrategy::Failover })
+    );
+}
+
+#[test]
+fn weighted_needs_every_weight() {
+    assert_eq!(validate_rotation(&format(RotationStrategy::Weighted, &[Some(2), Some(0)])), Ok(()));
+
+    assert_eq!(
+        validate_rotation(&format(RotationStrategy::Weighted, &[Some(2), None])),
+        Err(RotationError::MissingWeight(1))
+    );
+
+    assert_eq!(
+        validate_rotation(&format(RotationStrategy::Weighted, &[Some(0), Some(0)])),
+        Err(RotationError::AllWeightsZero)
+    );
+}
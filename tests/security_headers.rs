@@ -0,0 +1,113 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Checks the security headers on a server rendered page and on a static file.
+// Run with `cargo test --features ssr`.
+
+#![cfg(feature = "ssr")]
+
+use actix_files::Files;
+use actix_web::http::header;
+use actix_web::{middleware, test};
+use leptos::prelude::get_configuration;
+use leptos_actix::{LeptosRoutes, generate_route_list};
+use tulip::app::{App, shell};
+
+const ALWAYS_PRESENT: [&str; 6] = [
+    "content-security-policy",
+    "strict-transport-security",
+    "x-frame-options",
+    "referrer-policy",
+    "permissions-policy",
+    "x-content-type-options",
+];
+
+macro_rules! test_app {
+    () => {{
+        let leptos_options = get_configuration(Some("Cargo.toml")).unwrap().leptos_options;
+        let routes = generate_route_list(App);
+
+        test::init_service(
+            actix_web::App::new()
+                .leptos_routes(routes, {
+                    let leptos_options = leptos_options.clone();
+                    move || shell(leptos_options.clone())
+                })
+                .service(Files::new("/", "assets"))
+                .wrap(middleware::from_fn(tulip::security::security_headers)),
+        )
+        .await
+    }};
+}
+
+fn header_value<B>(response: &actix_web::dev::ServiceResponse<B>, name: &str) -> String {
+    response
+        .headers()
+        .get(name)
+        .unwrap_or_else(|| panic!("missing {}", name))
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+#[actix_web::test]
+async fn ssr_page_has_headers_and_matching_nonce() {
+    let app = test_app!();
+
+    let response = test::call_service(&app, test::TestRequest::get().uri("/help").to_request()).await;
+    assert!(response.status().is_success());
+
+    for name in ALWAYS_PRESENT {
+        header_value(&response, name);
+    }
+
+    let csp = header_value(&response, "content-security-policy");
+    // fonts, icons and images are all served from assets/
+    assert!(!csp.contains("https://"));
+    assert!(csp.contains("frame-ancestors 'none'"));
+
+    let script_src = csp.split("; ").find(|x| x.starts_with("script-src ")).unwrap();
+    assert!(!script_src.contains("'unsafe-inline'"));
+
+    let nonce = csp
+        .split("'nonce-")
+        .nth(1)
+        .and_then(|x| x.split('\'').next())
+        .expect("page CSP has no nonce")
+        .to_string();
+
+    let body = String::from_utf8(test::read_body(response).await.to_vec()).unwrap();
+
+    // the hydration scripts carry the nonce from the header
+    assert!(body.contains(&format!("nonce=\"{}\"", nonce)));
+    assert!(!body.contains("onclick="));
+}
+
+#[actix_web::test]
+async fn each_page_gets_its_own_nonce() {
+    let app = test_app!();
+
+    let first = test::call_service(&app, test::TestRequest::get().uri("/").to_request()).await;
+    let second = test::call_service(&app, test::TestRequest::get().uri("/").to_request()).await;
+
+    assert_ne!(
+        header_value(&first, "content-security-policy"),
+        header_value(&second, "content-security-policy")
+    );
+}
+
+#[actix_web::test]
+async fn static_file_has_headers_without_nonce() {
+    let app = test_app!();
+
+    let response = test::call_service(&app, test::TestRequest::get().uri("/favicon.svg").to_request()).await;
+    assert!(response.status().is_success());
+
+    for name in ALWAYS_PRESENT {
+        header_value(&response, name);
+    }
+
+    assert!(!header_value(&response, header::CONTENT_SECURITY_POLICY.as_str()).contains("'nonce-"));
+    assert_eq!(header_value(&response, "x-content-type-options"), "nosniff");
+}
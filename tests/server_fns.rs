@@ -0,0 +1,642 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Calls load_realtime_keys and submit_data against a local Birch stub.
+// Run with `cargo test --features ssr`.
+
+#![cfg(feature = "ssr")]
+
+use actix_web::{HttpRequest, HttpResponse, HttpServer, web};
+use std::os::unix::fs::PermissionsExt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, Once, OnceLock};
+use tulip::app::{KeyFormat, KeyResponse, SubmitOutcome, load_realtime_keys, submit_data};
+use tulip::auth::Role;
+use tulip::effective_config::{FeedUrlKind, load_effective_config};
+use tulip::feeds::load_public_feed;
+use tulip::ingestion::{StaleReason, load_ingestion_status};
+use tulip::interval::FetchInterval;
+
+const EDITOR: &str = "editor@example.com";
+const VIEWER: &str = "viewer@example.com";
+const BIRCH_PASSWORD: &str = "birch-password";
+
+/// getrealtimekeys answers 503 for this account.
+const UNAVAILABLE: &str = "unavailable@example.com";
+/// getrealtimekeys answers 200 with a body that is not a KeyResponse.
+const GARBLED: &str = "garbled@example.com";
+/// A viewer whose getrealtimekeys calls are counted, so no other test adds to the count.
+const COUNTED: &str = "counted@example.com";
+
+/// setrealtimekey answers 500 for this feed.
+const BROKEN_FEED_ID: &str = "f-broken~rt";
+
+/// A getrealtimekeys response as Birch sends it.
+const BIRCH_KEYS_JSON: &str = r#"{
+    "passwords": {
+        "f-test~rt": {
+            "passwords": {
+                "key_formats": [{"Header": "api_key"}],
+                "passwords": [{"password": ["stub-secret-key"], "creator_email": "editor@example.com"}],
+                "override_schedule_url": null,
+                "override_realtime_vehicle_positions": null,
+                "override_realtime_trip_updates": null,
+                "override_alerts": null
+            },
+            "fetch_interval_ms": 5000
+        },
+        "f-9-bay~area~rt": {
+            "passwords": {
+                "key_formats": [{"UrlQuery": "api_key"}],
+                "passwords": [
+                    {"password": ["first"], "creator_email": "editor@example.com", "created_at": "2024-03-01"},
+                    {"password": ["second"], "creator_email": "editor@example.com"}
+                ],
+                "override_schedule_url": null,
+                "override_realtime_vehicle_positions": "http://api.511.org/transit/vehiclepositions",
+                "override_realtime_trip_updates": null,
+                "override_alerts": null
+            },
+            "fetch_interval_ms": null
+        },
+        "f-broken~rt": {"passwords": null, "fetch_interval_ms": null}
+    }
+}"#;
+
+/// getrealtimeconfig for the Bay Area feed, which overrides only the vehicle positions URL.
+const BAY_AREA_CONFIG_JSON: &str = r#"{
+    "schedule_url": "https://511.org/gtfs.zip",
+    "realtime_vehicle_positions": "http://api.511.org/transit/vehiclepositions",
+    "realtime_trip_updates": "http://api.511.org/transit/tripupdates",
+    "realtime_alerts": null,
+    "fetch_interval_ms": 10000,
+    "key_formats": [{"UrlQuery": "api_key"}],
+    "password_count": 2
+}"#;
+
+/// getrealtimeconfig for the test feed, where Alpenrose still has an older interval than Birch's 5000.
+const TEST_FEED_CONFIG_JSON: &str = r#"{
+    "schedule_url": null,
+    "realtime_vehicle_positions": null,
+    "realtime_trip_updates": "https://agency.example.com/trips",
+    "realtime_alerts": null,
+    "fetch_interval_ms": 3000,
+    "key_formats": [{"Header": "api_key"}],
+    "password_count": 1
+}"#;
+
+const VALID_PASSWORD: &str = r#"Some((key_formats: [Header("api_key")], passwords: [(password: ["new-secret-key"], creator_email: "editor@example.com")], override_schedule_url: None, override_realtime_vehicle_positions: None, override_realtime_trip_updates: None, override_alerts: None))"#;
+
+static SETUP: Once = Once::new();
+static BIRCH_URL: OnceLock<String> = OnceLock::new();
+
+/// Bodies the stub received on setrealtimekey, as feed id and RON.
+static SUBMISSIONS: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+/// How often getrealtimekeys was called for COUNTED.
+static COUNTED_KEY_FETCHES: AtomicUsize = AtomicUsize::new(0);
+
+async fn stub_get_realtime_keys(form: web::Form<std::collections::HashMap<String, String>>) -> HttpResponse {
+    let email = form.get("email").map(|x| x.as_str()).unwrap_or_default();
+    let password = form.get("password").map(|x| x.as_str()).unwrap_or_default();
+
+    if email == COUNTED {
+        COUNTED_KEY_FETCHES.fetch_add(1, Ordering::SeqCst);
+    }
+
+    match (email, password) {
+        (UNAVAILABLE, _) => HttpResponse::ServiceUnavailable().body("Birch is down"),
+        (GARBLED, _) => HttpResponse::Ok().body("<html>not json</html>"),
+        (EDITOR | VIEWER | COUNTED, BIRCH_PASSWORD) => HttpResponse::Ok()
+            .content_type("application/json")
+            .body(BIRCH_KEYS_JSON),
+        _ => HttpResponse::Unauthorized().body("Unauthorized"),
+    }
+}
+
+async fn stub_set_realtime_key(req: HttpRequest, feed_id: web::Path<String>, body: String) -> HttpResponse {
+    let header = |name: &str| req.headers().get(name).and_then(|x| x.to_str().ok()).unwrap_or_default();
+
+    if header("password") != BIRCH_PASSWORD {
+        return HttpResponse::Unauthorized().body("Unauthorized");
+    }
+
+    if feed_id.as_str() == BROKEN_FEED_ID {
+        return HttpResponse::InternalServerError().body("Internal Server Error");
+    }
+
+    SUBMISSIONS.lock().unwrap().push((feed_id.into_inner(), body));
+    HttpResponse::Ok().body("OK")
+}
+
+async fn stub_get_realtime_config(req: HttpRequest, feed_id: web::Path<String>) -> HttpResponse {
+    if req.headers().get("password").and_then(|x| x.to_str().ok()) != Some(BIRCH_PASSWORD) {
+        return HttpResponse::Unauthorized().body("Unauthorized");
+    }
+
+    match feed_id.as_str() {
+        "f-9-bay~area~rt" => HttpResponse::Ok().content_type("application/json").body(BAY_AREA_CONFIG_JSON),
+        "f-test~rt" => HttpResponse::Ok().content_type("application/json").body(TEST_FEED_CONFIG_JSON),
+        _ => HttpResponse::NotFound().body("No such feed"),
+    }
+}
+
+/// Alpenrose loaded keys long ago and the agency now rejects them.
+async fn stub_get_realtime_status(req: HttpRequest) -> HttpResponse {
+    if req.headers().get("password").and_then(|x| x.to_str().ok()) != Some(BIRCH_PASSWORD) {
+        return HttpResponse::Unauthorized().body("Unauthorized");
+    }
+
+    let now_ms = chrono::Utc::now().timestamp_millis();
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "feeds": {
+            "f-status~rt": {
+                "last_success_ms": now_ms - 60_000,
+                "last_failure_ms": now_ms - 2_000,
+                "keys_loaded_ms": 0,
+                "recent_attempts": [
+                    {"time_ms": now_ms - 2_000, "feed_type": "vehicle", "http_status": 401, "bytes": 0, "parse_error": null, "error": null, "password_index": 0},
+                    {"time_ms": now_ms - 60_000, "feed_type": "vehicle", "http_status": 200, "bytes": 4096, "parse_error": null, "error": null, "password_index": 0}
+                ]
+            }
+        }
+    }))
+}
+
+/// Starts the stub on its own thread and points Tulip at it, before the config is first loaded.
+fn setup() {
+    SETUP.call_once(|| {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let birch_url = format!("http://{}", listener.local_addr().unwrap());
+
+        std::thread::spawn(move || {
+            actix_web::rt::System::new().block_on(async move {
+                HttpServer::new(|| {
+                    actix_web::App::new()
+                        .route("/getrealtimekeys", web::post().to(stub_get_realtime_keys))
+                        .route("/setrealtimekey/{feed_id}/", web::post().to(stub_set_realtime_key))
+                        .route("/getrealtimeconfig/{feed_id}/", web::get().to(stub_get_realtime_config))
+                        .route("/getrealtimestatus", web::get().to(stub_get_realtime_status))
+                })
+                .workers(1)
+                .listen(listener)
+                .unwrap()
+                .run()
+                .await
+                .unwrap()
+            })
+        });
+
+        let dir = std::env::temp_dir().join(format!("tulip_server_fns_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let config = format!(
+            r#"(
+                birch_url: "{}",
+                roles: {{ "{}": KeyEditor }},
+                default_role: Viewer,
+                birch_email: Some("{}"),
+                birch_password: Some("{}"),
+                audit_log_file: "{}",
+                history_file: "{}",
+                change_requests_file: "{}",
+                rate_limit: (free_failed_logins: 100000),
+            )"#,
+            birch_url,
+            EDITOR,
+            EDITOR,
+            BIRCH_PASSWORD,
+            dir.join("audit.jsonl").display(),
+            dir.join("history.jsonl").display(),
+            dir.join("change_requests.ron").display(),
+        );
+
+        let path = dir.join("tulip.ron");
+        std::fs::write(&path, config).unwrap();
+
+        // SAFETY: runs once, before any test reads the environment
+        unsafe { std::env::set_var("TULIP_CONFIG", &path) };
+
+        BIRCH_URL.set(birch_url).unwrap();
+    });
+}
+
+fn submissions_for(feed_id: &str) -> Vec<String> {
+    SUBMISSIONS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(x, _)| x == feed_id)
+        .map(|(_, body)| body.clone())
+        .collect()
+}
+
+#[test]
+fn key_response_decodes_birch_json() {
+    let response: KeyResponse = serde_json::from_str(BIRCH_KEYS_JSON).unwrap();
+
+    assert_eq!(response.passwords.len(), 3);
+
+    let test_feed = &response.passwords["f-test~rt"];
+    let passwords = test_feed.passwords.as_ref().unwrap();
+    assert_eq!(passwords.key_formats, vec![KeyFormat::Header(String::from("api_key"))]);
+    assert_eq!(passwords.passwords[0].password, vec![String::from("stub-secret-key")]);
+    assert_eq!(test_feed.fetch_interval_ms, Some(5000));
+
+    let bay_area = &response.passwords["f-9-bay~area~rt"];
+    let passwords = bay_area.passwords.as_ref().unwrap();
+    assert_eq!(passwords.key_formats, vec![KeyFormat::UrlQuery(String::from("api_key"))]);
+    assert_eq!(passwords.passwords.len(), 2);
+    assert_eq!(passwords.passwords[0].created_at, chrono::NaiveDate::from_ymd_opt(2024, 3, 1));
+    assert_eq!(bay_area.fetch_interval_ms, None);
+
+    assert!(response.passwords["f-broken~rt"].passwords.is_none());
+}
+
+#[actix_web::test]
+async fn load_returns_redacted_keys() {
+    setup();
+
+    let keys = load_realtime_keys(EDITOR.to_string(), BIRCH_PASSWORD.to_string())
+        .await
+        .unwrap()
+        .expect("Birch accepted the credentials");
+
+    assert_eq!(keys.role, Role::KeyEditor);
+    assert_eq!(&keys.birch_url, BIRCH_URL.get().unwrap());
+    assert_eq!(keys.default_fetch_interval_ms, 10_000);
+    assert_eq!(keys.passwords.len(), 3);
+
+    let test_feed = &keys.passwords["f-test~rt"];
+    assert_eq!(test_feed.fetch_interval_ms, Some(5000));
+
+    let passwords = test_feed.passwords.as_ref().unwrap();
+    assert_eq!(passwords.key_formats, vec![KeyFormat::Header(String::from("api_key"))]);
+    assert!(passwords.contains_redacted());
+    assert!(!format!("{:?}", keys).contains("stub-secret-key"));
+}
+
+#[actix_web::test]
+async fn load_asks_birch_for_the_keys_once() {
+    setup();
+
+    load_realtime_keys(COUNTED.to_string(), BIRCH_PASSWORD.to_string())
+        .await
+        .unwrap()
+        .expect("Birch accepted the credentials");
+
+    // confirming the login already returned the keys
+    assert_eq!(COUNTED_KEY_FETCHES.load(Ordering::SeqCst), 1);
+}
+
+#[actix_web::test]
+async fn load_gives_the_configured_role() {
+    setup();
+
+    let keys = load_realtime_keys(VIEWER.to_string(), BIRCH_PASSWORD.to_string())
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(keys.role, Role::Viewer);
+}
+
+#[actix_web::test]
+async fn load_is_none_when_birch_rejects_the_login() {
+    setup();
+
+    let keys = load_realtime_keys(EDITOR.to_string(), String::from("wrong-password")).await.unwrap();
+
+    assert!(keys.is_none());
+}
+
+#[actix_web::test]
+async fn load_fails_when_birch_errors() {
+    setup();
+
+    let err = load_realtime_keys(UNAVAILABLE.to_string(), BIRCH_PASSWORD.to_string())
+        .await
+        .unwrap_err();
+
+    assert!(err.to_string().contains("503"), "{}", err);
+    assert!(err.to_string().contains("Birch is down"), "{}", err);
+}
+
+#[actix_web::test]
+async fn load_fails_when_birch_sends_something_else() {
+    setup();
+
+    let result = load_realtime_keys(GARBLED.to_string(), BIRCH_PASSWORD.to_string()).await;
+
+    assert!(result.is_err());
+}
+
+#[actix_web::test]
+async fn submit_sends_ron_to_birch() {
+    setup();
+
+    let outcome = submit_data(
+        EDITOR.to_string(),
+        BIRCH_PASSWORD.to_string(),
+        String::from("f-test~rt"),
+        VALID_PASSWORD.to_string(),
+        String::from("Some(1000)"),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(outcome, SubmitOutcome::Applied);
+
+    let submissions = submissions_for("f-test~rt");
+    assert_eq!(submissions.len(), 1);
+
+    let sent: tulip::app::EachPasswordRow = ron::from_str(&submissions[0]).unwrap();
+    assert_eq!(sent.fetch_interval_ms, Some(1000));
+    assert_eq!(sent.passwords.unwrap().passwords[0].password, vec![String::from("new-secret-key")]);
+
+    // the history now holds the new key, readable only by Tulip's user
+    let history_file = std::fs::metadata(&tulip::config::config().history_file).unwrap();
+    assert_eq!(history_file.permissions().mode() & 0o777, 0o600);
+}
+
+#[actix_web::test]
+async fn submit_is_unauthorised_when_birch_rejects_the_login() {
+    setup();
+
+    let outcome = submit_data(
+        EDITOR.to_string(),
+        String::from("wrong-password"),
+        String::from("f-9-bay~area~rt"),
+        String::from("None"),
+        String::from("None"),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(outcome, SubmitOutcome::Unauthorised);
+    assert!(submissions_for("f-9-bay~area~rt").is_empty());
+}
+
+#[actix_web::test]
+async fn submit_fails_when_birch_errors() {
+    setup();
+
+    let err = submit_data(
+        EDITOR.to_string(),
+        BIRCH_PASSWORD.to_string(),
+        BROKEN_FEED_ID.to_string(),
+        String::from("None"),
+        String::from("None"),
+    )
+    .await
+    .unwrap_err();
+
+    assert!(err.to_string().contains("Data did not submit correctly"), "{}", err);
+}
+
+#[actix_web::test]
+async fn submit_rejects_a_password_that_does_not_parse() {
+    setup();
+
+    let result = submit_data(
+        EDITOR.to_string(),
+        BIRCH_PASSWORD.to_string(),
+        String::from("f-bad-password~rt"),
+        String::from("Some((key_formats: [Header(\"api_key\")]"),
+        String::from("None"),
+    )
+    .await;
+
+    assert!(result.is_err());
+    assert!(submissions_for("f-bad-password~rt").is_empty());
+}
+
+#[actix_web::test]
+async fn submit_rejects_weighted_rotation_without_weights() {
+    setup();
+
+    let err = submit_data(
+        EDITOR.to_string(),
+        BIRCH_PASSWORD.to_string(),
+        String::from("f-unweighted~rt"),
+        VALID_PASSWORD.replace("override_alerts: None", "override_alerts: None, rotation: Weighted"),
+        String::from("None"),
+    )
+    .await
+    .unwrap_err();
+
+    assert!(err.to_string().contains("needs a weight"), "{}", err);
+    assert!(submissions_for("f-unweighted~rt").is_empty());
+}
+
+#[actix_web::test]
+async fn submit_rejects_an_interval_that_does_not_parse() {
+    setup();
+
+    let result = submit_data(
+        EDITOR.to_string(),
+        BIRCH_PASSWORD.to_string(),
+        String::from("f-bad-interval~rt"),
+        String::from("None"),
+        String::from("every minute"),
+    )
+    .await;
+
+    assert!(result.is_err());
+    assert!(submissions_for("f-bad-interval~rt").is_empty());
+}
+
+#[actix_web::test]
+async fn submit_enforces_the_interval_bounds() {
+    setup();
+
+    let err = submit_data(
+        EDITOR.to_string(),
+        BIRCH_PASSWORD.to_string(),
+        String::from("f-fast-interval~rt"),
+        String::from("None"),
+        String::from("100ms"),
+    )
+    .await
+    .unwrap_err();
+
+    assert!(err.to_string().contains("at least 1s"), "{}", err);
+    assert!(submissions_for("f-fast-interval~rt").is_empty());
+}
+
+#[actix_web::test]
+async fn submit_leaves_the_default_interval_to_alpenrose() {
+    setup();
+
+    submit_data(
+        EDITOR.to_string(),
+        BIRCH_PASSWORD.to_string(),
+        String::from("f-default-interval~rt"),
+        String::from("None"),
+        String::from("default"),
+    )
+    .await
+    .unwrap();
+
+    let sent: tulip::app::EachPasswordRow = ron::from_str(&submissions_for("f-default-interval~rt")[0]).unwrap();
+    assert_eq!(sent.fetch_interval_ms, None);
+}
+
+#[actix_web::test]
+async fn viewers_cannot_submit() {
+    setup();
+
+    let result = submit_data(
+        VIEWER.to_string(),
+        BIRCH_PASSWORD.to_string(),
+        String::from("f-viewer~rt"),
+        String::from("None"),
+        String::from("None"),
+    )
+    .await;
+
+    assert!(result.is_err());
+    assert!(submissions_for("f-viewer~rt").is_empty());
+}
+
+#[actix_web::test]
+async fn effective_config_marks_overrides_and_defaults() {
+    setup();
+
+    let config = load_effective_config(VIEWER.to_string(), BIRCH_PASSWORD.to_string(), String::from("f-9-bay~area~rt"))
+        .await
+        .unwrap()
+        .expect("Birch accepted the credentials");
+
+    let url = |kind: FeedUrlKind| config.urls.iter().find(|x| x.kind == kind).unwrap().clone();
+
+    assert_eq!(url(FeedUrlKind::Vehicles).url.as_deref(), Some("http://api.511.org/transit/vehiclepositions"));
+    assert!(url(FeedUrlKind::Vehicles).overridden);
+    assert_eq!(url(FeedUrlKind::TripUpdates).url.as_deref(), Some("http://api.511.org/transit/tripupdates"));
+    assert!(!url(FeedUrlKind::TripUpdates).overridden);
+    assert!(!url(FeedUrlKind::Schedule).overridden);
+    assert_eq!(url(FeedUrlKind::Alerts).url, None);
+    assert!(config.urls.iter().all(|x| x.pending_override.is_none()));
+
+    assert_eq!(config.interval, FetchInterval::Default);
+    assert_eq!(config.fetch_interval_ms, 10_000);
+    assert_eq!(config.key_formats, vec![KeyFormat::UrlQuery(String::from("api_key"))]);
+    assert_eq!(config.password_count, 2);
+}
+
+#[actix_web::test]
+async fn effective_config_shows_what_alpenrose_has_not_picked_up() {
+    setup();
+
+    let config = load_effective_config(EDITOR.to_string(), BIRCH_PASSWORD.to_string(), String::from("f-test~rt"))
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(config.interval, FetchInterval::Every { ms: 5000 });
+    assert_eq!(config.fetch_interval_ms, 3000);
+}
+
+#[actix_web::test]
+async fn effective_config_errors_for_unknown_feeds() {
+    setup();
+
+    let err = load_effective_config(EDITOR.to_string(), BIRCH_PASSWORD.to_string(), String::from("f-unknown~rt"))
+        .await
+        .unwrap_err();
+
+    assert!(err.to_string().contains("no config for feed f-unknown~rt"), "{}", err);
+}
+
+#[actix_web::test]
+async fn effective_config_is_none_when_birch_rejects_the_login() {
+    setup();
+
+    let config = load_effective_config(EDITOR.to_string(), String::from("wrong-password"), String::from("f-test~rt"))
+        .await
+        .unwrap();
+
+    assert!(config.is_none());
+}
+
+#[actix_web::test]
+async fn feed_ids_cannot_leave_their_path_segment() {
+    setup();
+
+    for feed_id in ["..", ".", "../getrealtimekeys", "f-test~rt/../f-escaped~rt", "f-test~rt?x=1", "f-test~rt#", "f test", "%2e%2e"] {
+        let err = load_effective_config(EDITOR.to_string(), BIRCH_PASSWORD.to_string(), feed_id.to_string())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("is not a valid feed id"), "{}: {}", feed_id, err);
+
+        let result = submit_data(
+            EDITOR.to_string(),
+            BIRCH_PASSWORD.to_string(),
+            feed_id.to_string(),
+            VALID_PASSWORD.to_string(),
+            String::from("None"),
+        )
+        .await;
+        assert!(result.is_err(), "{}", feed_id);
+    }
+
+    // without the check, the URL would resolve to /setrealtimekey/f-escaped~rt/
+    assert!(submissions_for("f-escaped~rt").is_empty());
+}
+
+#[actix_web::test]
+async fn ingestion_status_explains_a_key_change_alpenrose_has_not_loaded() {
+    setup();
+
+    submit_data(
+        EDITOR.to_string(),
+        BIRCH_PASSWORD.to_string(),
+        String::from("f-status~rt"),
+        String::from("None"),
+        String::from("default"),
+    )
+    .await
+    .unwrap();
+
+    let feeds = load_ingestion_status(VIEWER.to_string(), BIRCH_PASSWORD.to_string())
+        .await
+        .unwrap()
+        .expect("Birch accepted the credentials");
+
+    let summary = &feeds["f-status~rt"];
+
+    assert!(summary.stale);
+    assert!(summary.key_changed_ms.is_some());
+    assert!(matches!(summary.reasons[0], StaleReason::KeysNotReloaded { loaded_ms: Some(0), .. }));
+    assert_eq!(
+        summary.reasons[1],
+        StaleReason::KeyRejected { status: 401, password_index: Some(0) }
+    );
+    assert_eq!(summary.status.recent_attempts.len(), 2);
+}
+
+#[actix_web::test]
+async fn ingestion_status_is_none_when_birch_rejects_the_login() {
+    setup();
+
+    let feeds = load_ingestion_status(EDITOR.to_string(), String::from("wrong-password")).await.unwrap();
+
+    assert!(feeds.is_none());
+}
+
+#[actix_web::test]
+async fn feed_pages_come_from_birch_status() {
+    setup();
+
+    let feed = load_public_feed(String::from("f-status~rt")).await.unwrap().expect("Birch has the feed");
+    assert_eq!(feed.feed_id, "f-status~rt");
+    assert_eq!(&feed.birch_url, BIRCH_URL.get().unwrap());
+    assert!(feed.last_success_ms.is_some());
+
+    assert_eq!(load_public_feed(String::from("f-unknown~rt")).await.unwrap(), None);
+
+    let paths = tulip::sitemap::public_entries().await.into_iter().map(|x| x.path).collect::<Vec<_>>();
+    assert_eq!(paths, vec!["/", "/help", "/feed/f-status~rt"]);
+}
@@ -0,0 +1,108 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Checks sitemaps are split into files of at most 50,000 URLs.
+// Run with `cargo test --features ssr`.
+
+#![cfg(feature = "ssr")]
+
+use chrono::NaiveDate;
+use tulip::feeds::PublicFeed;
+use tulip::sitemap::{MAX_URLS_PER_SITEMAP, SitemapEntry, feed_entries, root_sitemap_xml, sitemap_page_xml};
+
+const BASE_URL: &str = "https://tulip.example";
+
+fn entries(count: usize) -> Vec<SitemapEntry> {
+    (0..count)
+        .map(|index| SitemapEntry {
+            path: format!("/stop/{}", index),
+            lastmod: NaiveDate::from_ymd_opt(2025, 1, 1 + (index % 28) as u32),
+        })
+        .collect()
+}
+
+fn url_count(xml: &str) -> usize {
+    xml.matches("<url>").count()
+}
+
+#[test]
+fn a_full_sitemap_is_not_split() {
+    let entries = entries(MAX_URLS_PER_SITEMAP);
+    let root = root_sitemap_xml(BASE_URL, &entries);
+
+    assert!(root.contains("<urlset"));
+    assert_eq!(url_count(&root), MAX_URLS_PER_SITEMAP);
+    assert_eq!(sitemap_page_xml(BASE_URL, &entries, 0), None);
+}
+
+#[test]
+fn one_url_over_becomes_an_index_of_two() {
+    let entries = entries(MAX_URLS_PER_SITEMAP + 1);
+    let root = root_sitemap_xml(BASE_URL, &entries);
+
+    assert!(root.contains("<sitemapindex"));
+    assert!(root.contains("<loc>https://tulip.example/sitemap-0.xml</loc>"));
+    assert!(root.contains("<loc>https://tulip.example/sitemap-1.xml</loc>"));
+    assert!(!root.contains("sitemap-2.xml"));
+    assert!(root.contains("<lastmod>2025-01-28</lastmod>"));
+
+    let first = sitemap_page_xml(BASE_URL, &entries, 0).unwrap();
+    assert_eq!(url_count(&first), MAX_URLS_PER_SITEMAP);
+
+    let second = sitemap_page_xml(BASE_URL, &entries, 1).unwrap();
+    assert_eq!(url_count(&second), 1);
+    assert!(second.contains(&format!("<loc>https://tulip.example/stop/{}</loc>", MAX_URLS_PER_SITEMAP)));
+}
+
+#[test]
+fn pages_past_the_last_are_missing() {
+    let entries = entries(2 * MAX_URLS_PER_SITEMAP);
+
+    assert!(sitemap_page_xml(BASE_URL, &entries, 1).is_some());
+    assert_eq!(sitemap_page_xml(BASE_URL, &entries, 2), None);
+    assert_eq!(sitemap_page_xml(BASE_URL, &entries, usize::MAX), None);
+}
+
+#[test]
+fn small_sitemaps_have_no_pages() {
+    let entries = entries(2);
+
+    assert_eq!(url_count(&root_sitemap_xml(BASE_URL, &entries)), 2);
+    assert_eq!(sitemap_page_xml(BASE_URL, &entries, 0), None);
+    assert_eq!(sitemap_page_xml(BASE_URL, &[], 0), None);
+}
+
+#[test]
+fn feeds_are_dated_by_their_last_successful_fetch() {
+    let feed = |feed_id: &str, last_success_ms: Option<i64>| {
+        let feed = PublicFeed {
+            feed_id: feed_id.to_string(),
+            last_success_ms,
+            birch_url: String::from("https://birch.example"),
+        };
+        (feed_id.to_string(), feed)
+    };
+
+    let feeds = [
+        // 2024-03-01 23:59:59 UTC
+        feed("f-9-bay~area~rt", Some(1_709_337_599_000)),
+        feed("f-test~rt", None),
+    ]
+    .into_iter()
+    .collect();
+
+    assert_eq!(
+        feed_entries(&feeds),
+        vec![
+            SitemapEntry {
+                path: String::from("/feed/f-9-bay~area~rt"),
+                lastmod: NaiveDate::from_ymd_opt(2024, 3, 1),
+            },
+            SitemapEntry {
+                path: String::from("/feed/f-test~rt"),
+                lastmod: None,
+            },
+        ]
+    );
+}
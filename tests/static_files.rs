@@ -0,0 +1,40 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Checks the Cache-Control on files served from assets/.
+// Run with `cargo test --features ssr`.
+
+#![cfg(feature = "ssr")]
+
+use actix_files::Files;
+use actix_web::middleware;
+use actix_web::test::{TestRequest, call_service, init_service, read_body};
+use tulip::static_files::cache_control;
+
+#[test]
+fn vendored_files_are_cached_and_the_bundle_revalidated() {
+    assert_eq!(cache_control("/fonts/barlow-latin-400-normal.woff2"), Some("public, max-age=2592000"));
+    assert_eq!(cache_control("/images/KLine_FairviewHeights_KimSchoenstadt2.jpeg"), Some("public, max-age=2592000"));
+    assert_eq!(cache_control("/favicon.svg"), Some("public, max-age=2592000"));
+    assert_eq!(cache_control("/pkg/catenarytulip.wasm"), Some("no-cache"));
+    assert_eq!(cache_control("/realtimekeys"), None);
+    assert_eq!(cache_control("/api/load_realtime_keys"), None);
+}
+
+#[actix_web::test]
+async fn vendored_font_css_is_served_with_cache_headers() {
+    let app = init_service(
+        actix_web::App::new()
+            .service(Files::new("/", "assets"))
+            .wrap(middleware::from_fn(tulip::static_files::cache_headers)),
+    )
+    .await;
+
+    let response = call_service(&app, TestRequest::get().uri("/fonts/fonts.css").to_request()).await;
+    assert!(response.status().is_success());
+    assert!(response.headers().get("cache-control").unwrap().to_str().unwrap().contains("max-age="));
+
+    let body = String::from_utf8(read_body(response).await.to_vec()).unwrap();
+    assert!(!body.contains("googleapis"));
+}
@@ -0,0 +1,63 @@
+// Copyright Kyler Chin <kyler@catenarymaps.org>
+// Catenary Transit Initiatives
+// Attribution cannot be removed
+
+// Checks the local account store as written by the user management server functions.
+// Run with `cargo test --features ssr`.
+
+#![cfg(feature = "ssr")]
+
+use std::os::unix::fs::PermissionsExt;
+use tulip::auth::Role;
+use tulip::users::{UserAccount, change_password, check_login, hash_password};
+
+const OPERATOR: &str = "operator@example.com";
+const PASSWORD: &str = "correct horse battery";
+
+#[actix_web::test]
+async fn saved_accounts_are_readable_only_by_tulip() {
+    let dir = std::env::temp_dir().join(format!("tulip_users_test_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let users_file = dir.join("users.ron");
+    let config = format!(
+        r#"(
+            birch_url: "http://127.0.0.1:9",
+            users_file: Some("{}"),
+            birch_email: Some("tulip@example.com"),
+            birch_password: Some("service-password"),
+            audit_log_file: "{}",
+        )"#,
+        users_file.display(),
+        dir.join("audit.jsonl").display(),
+    );
+
+    let path = dir.join("tulip.ron");
+    std::fs::write(&path, config).unwrap();
+
+    // SAFETY: the only test in this binary, before the config is first loaded
+    unsafe { std::env::set_var("TULIP_CONFIG", &path) };
+
+    // a store written by hand, readable by everyone
+    let account = UserAccount {
+        email: OPERATOR.to_string(),
+        password_hash: hash_password(PASSWORD).unwrap(),
+        role: Role::KeyEditor,
+        disabled: false,
+    };
+    let users = std::collections::BTreeMap::from([(OPERATOR.to_string(), account)]);
+    std::fs::write(&users_file, ron::to_string(&users).unwrap()).unwrap();
+    std::fs::set_permissions(&users_file, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+    let changed = change_password(OPERATOR.to_string(), PASSWORD.to_string(), String::from("a longer new password"))
+        .await
+        .unwrap();
+    assert!(changed);
+
+    let mode = std::fs::metadata(&users_file).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+    assert!(!dir.join("users.ron.tmp").exists());
+
+    assert!(check_login(OPERATOR, "a longer new password").unwrap().is_some());
+    assert!(check_login(OPERATOR, PASSWORD).unwrap().is_none());
+}